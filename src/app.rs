@@ -0,0 +1,574 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use glfw::Context;
+
+use crate::capture;
+use crate::{
+    Action, Color, Config, Error, FixedTimestep, Font, FrameLimiter, FrameRecorder, FrameStats,
+    Framebuffer, GlProfile, GpuTimer, InputMap, PostProcessor, Quat, Renderer, Summary,
+    TRIANGLE_COLOR, TextRenderer, Transform, Vec2, Vec3, gl_get_string,
+};
+
+// Colors cycled through by `Action::NextColor` and `Action::PreviousColor`
+const COLOR_CYCLE: [Color; 7] = [
+    TRIANGLE_COLOR,
+    Color::RED,
+    Color::LIME,
+    Color::BLUE,
+    Color::YELLOW,
+    Color::CYAN,
+    Color::MAGENTA,
+];
+// Per key press or scroll step
+const MOVE_STEP: f32 = 0.05;
+const ROTATE_STEP: f32 = 5.0 * std::f32::consts::PI / 180.0;
+const ZOOM_STEP: f32 = 1.1;
+// How often the stats display is refreshed
+const STATS_INTERVAL: Duration = Duration::from_millis(500);
+// Top-left corner of the stats overlay, in pixels
+const STATS_POSITION: Vec2 = Vec2::new(8.0, 8.0);
+// Frame rate of recorded videos when neither a frame rate cap nor the
+// monitor refresh rate is known
+const RECORD_FPS: u32 = 60;
+
+// Offscreen framebuffers the scene is drawn into when the render scale is not
+// 1 or there are effects
+struct SceneTarget {
+    scale: f32,
+    framebuffer: Framebuffer,
+    // Single-sampled copy of a multisampled `framebuffer`, which can't be
+    // stretched or sampled directly
+    resolved: Option<Framebuffer>,
+    post: PostProcessor,
+}
+
+impl SceneTarget {
+    fn new(config: &Config, window_width: i32, window_height: i32) -> Result<Self, Error> {
+        let scale = config.render_scale;
+        let (width, height) = scaled_size(scale, window_width, window_height);
+        let resolved = if config.samples > 0 {
+            Some(Framebuffer::new(width, height, 0)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            scale,
+            framebuffer: Framebuffer::new(width, height, config.samples)?,
+            resolved,
+            post: PostProcessor::new(&config.effects, width, height)?,
+        })
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.framebuffer.size()
+    }
+
+    fn resize(&mut self, window_width: i32, window_height: i32) -> Result<(), Error> {
+        let (width, height) = scaled_size(self.scale, window_width, window_height);
+        self.framebuffer.resize(width, height)?;
+        if let Some(resolved) = &mut self.resolved {
+            resolved.resize(width, height)?;
+        }
+        self.post.resize(width, height)
+    }
+
+    // Applies the effects and stretches the result over the window, leaving
+    // the window bound
+    fn present(&self, window_width: i32, window_height: i32, time: f32) {
+        let mut frame = &self.framebuffer;
+        if let Some(resolved) = &self.resolved {
+            self.framebuffer.resolve(resolved);
+            frame = resolved;
+        }
+        if let Some(texture) = frame.color_texture()
+            && let Some(output) = self.post.apply(texture, time)
+        {
+            frame = output;
+        }
+        frame.blit_to_screen(window_width as u32, window_height as u32);
+    }
+}
+
+// At least 1x1, a minimized window has a zero size
+fn scaled_size(scale: f32, width: i32, height: i32) -> (u32, u32) {
+    let scaled = |size: i32| (size as f32 * scale).round().max(1.0) as u32;
+    (scaled(width), scaled(height))
+}
+
+// Owns the GLFW window, its GL context and the `Renderer` drawing into it
+pub struct App {
+    glfw: glfw::Glfw,
+    window: glfw::PWindow,
+    events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
+    renderer: Renderer,
+    text: TextRenderer,
+    // None draws the scene straight into the window
+    scene_target: Option<SceneTarget>,
+    input: InputMap,
+    log_events: bool,
+    color_index: usize,
+    // Position and size restored when leaving fullscreen
+    windowed_rect: (i32, i32, i32, i32),
+    vsync: bool,
+    timestep: FixedTimestep,
+    limiter: FrameLimiter,
+    stats: FrameStats,
+    gpu_timer: GpuTimer,
+    show_stats: bool,
+    stats_shown_at: Instant,
+    stats_text: String,
+    stats_summary: bool,
+    stats_csv: Option<PathBuf>,
+    capture_dir: PathBuf,
+    // Set by `Action::Screenshot`, saved once the frame is drawn
+    screenshot_requested: bool,
+    recorder: Option<FrameRecorder>,
+    record_fps: u32,
+}
+
+impl App {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        use glfw::fail_on_errors;
+
+        let render_scale = config.render_scale;
+        if !render_scale.is_finite() || render_scale <= 0.0 {
+            return Err(Error::Args(format!("Invalid render scale: {render_scale}")));
+        }
+
+        // GLFW init, detecting errors
+        let mut glfw = glfw::init(fail_on_errors!())?;
+
+        // Necessary OpenGL version
+        let (major, minor) = config.gl_version;
+        glfw.window_hint(glfw::WindowHint::ContextVersion(major, minor));
+        let profile = match config.gl_profile {
+            GlProfile::Core => glfw::OpenGlProfileHint::Core,
+            GlProfile::Compat => glfw::OpenGlProfileHint::Compat,
+            GlProfile::Any => glfw::OpenGlProfileHint::Any,
+        };
+        glfw.window_hint(glfw::WindowHint::OpenGlProfile(profile));
+        // Required for core profiles on macOS
+        glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(
+            config.gl_profile == GlProfile::Core,
+        ));
+        // A scene drawn offscreen is multisampled in its own framebuffer,
+        // blits into a multisampled window are not allowed
+        let offscreen = render_scale != 1.0 || !config.effects.is_empty();
+        if config.samples > 0 && !offscreen {
+            glfw.window_hint(glfw::WindowHint::Samples(Some(config.samples)));
+        }
+
+        let (mut window, events) = glfw
+            .with_primary_monitor(|glfw, monitor| {
+                let mode = match monitor {
+                    Some(monitor) if config.fullscreen => glfw::WindowMode::FullScreen(monitor),
+                    _ => glfw::WindowMode::Windowed,
+                };
+                glfw.create_window(config.width, config.height, &config.title, mode)
+            })
+            .ok_or(Error::WindowCreation)?;
+        let (buffer_width, buffer_height) = window.get_framebuffer_size();
+        let windowed_rect = if config.fullscreen {
+            (100, 100, config.width as i32, config.height as i32)
+        } else {
+            let (x, y) = window.get_pos();
+            let (width, height) = window.get_size();
+            (x, y, width, height)
+        };
+
+        window.make_current();
+        glfw.set_swap_interval(swap_interval(config.vsync));
+        // Set window to receive events
+        window.set_key_polling(true);
+        window.set_mouse_button_polling(true);
+        window.set_scroll_polling(true);
+        window.set_framebuffer_size_polling(true);
+
+        // Load GL Lib
+        gl::load_with(|ptr| window.get_proc_address(ptr) as *const _);
+
+        if config.samples > 0 {
+            unsafe { gl::Enable(gl::MULTISAMPLE) };
+        }
+
+        let scene_target = offscreen
+            .then(|| SceneTarget::new(config, buffer_width, buffer_height))
+            .transpose()?;
+
+        let mut renderer = Renderer::new(config)?;
+        // Set view port to the window's buffer size, or the scene's
+        match &scene_target {
+            Some(target) => {
+                let (width, height) = target.size();
+                renderer.resize(width as i32, height as i32);
+            }
+            None => renderer.resize(buffer_width, buffer_height),
+        }
+        let text = TextRenderer::new(Font::from_config(config)?)?;
+        // Frames are duplicated or dropped to keep this rate, the one the
+        // window is expected to run at
+        let refresh_rate = glfw.with_primary_monitor(|_, monitor| {
+            monitor
+                .and_then(|monitor| monitor.get_video_mode())
+                .map(|mode| mode.refresh_rate)
+        });
+        let record_fps = match refresh_rate {
+            _ if config.max_fps > 0 => config.max_fps,
+            Some(rate) if config.vsync && rate > 0 => rate,
+            _ => RECORD_FPS,
+        };
+        let recorder = config
+            .record
+            .as_deref()
+            .map(|path| FrameRecorder::new(path, record_fps))
+            .transpose()?;
+
+        Ok(Self {
+            glfw,
+            window,
+            events,
+            renderer,
+            text,
+            scene_target,
+            input: InputMap::new(&config.bindings),
+            log_events: config.log_events,
+            color_index: 0,
+            windowed_rect,
+            vsync: config.vsync,
+            timestep: FixedTimestep::from_rate(config.update_rate),
+            limiter: FrameLimiter::new(config.max_fps),
+            // The rolling window is enough for the overlay
+            stats: if config.stats_summary || config.stats_csv.is_some() {
+                FrameStats::with_history()
+            } else {
+                FrameStats::new()
+            },
+            gpu_timer: GpuTimer::new(),
+            show_stats: config.stats,
+            stats_shown_at: Instant::now(),
+            stats_text: String::new(),
+            stats_summary: config.stats_summary,
+            stats_csv: config.stats_csv.clone(),
+            capture_dir: config.capture_dir.clone(),
+            screenshot_requested: false,
+            recorder,
+            record_fps,
+        })
+    }
+
+    pub fn window(&self) -> &glfw::Window {
+        &self.window
+    }
+
+    pub fn window_mut(&mut self) -> &mut glfw::Window {
+        &mut self.window
+    }
+
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    // Text queued here is drawn over the next frame
+    pub fn text_mut(&mut self) -> &mut TextRenderer {
+        &mut self.text
+    }
+
+    pub fn input(&self) -> &InputMap {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut InputMap {
+        &mut self.input
+    }
+
+    pub fn handle_event(&mut self, event: glfw::WindowEvent) {
+        if self.log_events {
+            println!("{event:?}");
+        }
+        match event {
+            glfw::WindowEvent::Close => self.window.set_should_close(true),
+            // Also fired when the window moves to a monitor with another scale
+            glfw::WindowEvent::FramebufferSize(width, height) => self.resize(width, height),
+            _ => {}
+        }
+
+        for action in self.input.actions(&event) {
+            self.perform(action);
+        }
+    }
+
+    // Fits the scene to a new window framebuffer size
+    fn resize(&mut self, width: i32, height: i32) {
+        let Some(target) = &mut self.scene_target else {
+            self.renderer.resize(width, height);
+            return;
+        };
+        match target.resize(width, height) {
+            Ok(()) => {
+                let (width, height) = target.size();
+                self.renderer.resize(width as i32, height as i32);
+            }
+            Err(e) => eprintln!("Failed to resize the scene: {e}"),
+        }
+    }
+
+    pub fn perform(&mut self, action: Action) {
+        let transform = self.renderer.transform_mut();
+        match action {
+            Action::Quit => self.window.set_should_close(true),
+            Action::MoveLeft => transform.translate(Vec3::new(-MOVE_STEP, 0.0, 0.0)),
+            Action::MoveRight => transform.translate(Vec3::new(MOVE_STEP, 0.0, 0.0)),
+            Action::MoveUp => transform.translate(Vec3::new(0.0, MOVE_STEP, 0.0)),
+            Action::MoveDown => transform.translate(Vec3::new(0.0, -MOVE_STEP, 0.0)),
+            Action::RotateLeft => transform.rotate(Quat::from_rotation_z(ROTATE_STEP)),
+            Action::RotateRight => transform.rotate(Quat::from_rotation_z(-ROTATE_STEP)),
+            Action::ZoomIn => transform.scale *= ZOOM_STEP,
+            Action::ZoomOut => transform.scale *= 1.0 / ZOOM_STEP,
+            Action::NextColor => self.cycle_color(1),
+            Action::PreviousColor => self.cycle_color(COLOR_CYCLE.len() - 1),
+            Action::ResetView => {
+                *transform = Transform::IDENTITY;
+            }
+            Action::ToggleWireframe => {
+                let wireframe = self.renderer.wireframe();
+                self.renderer.set_wireframe(!wireframe);
+            }
+            Action::ToggleFullscreen => self.toggle_fullscreen(),
+            Action::ToggleVsync => self.set_vsync(!self.vsync),
+            Action::ToggleStats => self.set_show_stats(!self.show_stats),
+            Action::Screenshot => self.screenshot_requested = true,
+            Action::ToggleRecording => self.toggle_recording(),
+        }
+    }
+
+    pub fn recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    // Records every following frame into `path`, see `FrameRecorder::new`.
+    // A recording already running is stopped first.
+    pub fn start_recording(&mut self, path: &Path) -> Result<(), Error> {
+        self.stop_recording()?;
+        self.recorder = Some(FrameRecorder::new(path, self.record_fps)?);
+        println!("Recording to {}", path.display());
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Result<(), Error> {
+        let Some(recorder) = self.recorder.take() else {
+            return Ok(());
+        };
+        let (frames, path) = (recorder.frames(), recorder.path().to_path_buf());
+        recorder.finish()?;
+        println!("Recorded {frames} frames to {}", path.display());
+        Ok(())
+    }
+
+    // Stops the recording, or starts one of numbered PNGs in a new directory
+    // of the capture directory
+    fn toggle_recording(&mut self) {
+        let result = if self.recording() {
+            self.stop_recording()
+        } else {
+            let name = format!("recording-{}", capture::timestamp(SystemTime::now()));
+            self.start_recording(&self.capture_dir.join(name))
+        };
+        if let Err(e) = result {
+            eprintln!("{e}");
+        }
+    }
+
+    // Saves the drawn frame as asked for by the screenshot action and the
+    // recorder. Reads the back buffer, so it has to run before the swap.
+    fn capture_frame(&mut self, width: i32, height: i32) {
+        let wanted = self.screenshot_requested || self.recorder.is_some();
+        // Nothing to read from a minimized window
+        if !wanted || width <= 0 || height <= 0 {
+            return;
+        }
+        let (width, height) = (width as u32, height as u32);
+        unsafe { gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0) };
+        let pixels = capture::read_pixels(width, height);
+
+        if std::mem::take(&mut self.screenshot_requested) {
+            let path = capture::screenshot_path(&self.capture_dir);
+            let saved = std::fs::create_dir_all(&self.capture_dir)
+                .map_err(|source| Error::Io {
+                    path: self.capture_dir.clone(),
+                    source,
+                })
+                .and_then(|()| capture::save_png(&path, width, height, &pixels));
+            match saved {
+                Ok(()) => println!("Saved {}", path.display()),
+                Err(e) => eprintln!("Screenshot failed: {e}"),
+            }
+        }
+        if let Some(recorder) = &mut self.recorder
+            && let Err(e) = recorder.record_realtime(width, height, &pixels)
+        {
+            eprintln!("Recording stopped: {e}");
+            self.recorder = None;
+        }
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.vsync = vsync;
+        self.glfw.set_swap_interval(swap_interval(vsync));
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    pub fn set_show_stats(&mut self, show_stats: bool) {
+        self.show_stats = show_stats;
+    }
+
+    // Queues the rolling frame statistics as a text overlay in the top-left
+    // corner. The numbers are refreshed every `STATS_INTERVAL` so they stay
+    // readable.
+    fn show_stats(&mut self) {
+        if self.stats_text.is_empty() || self.stats_shown_at.elapsed() >= STATS_INTERVAL {
+            self.stats_shown_at = Instant::now();
+            let ms = |summary: Option<Summary>| {
+                summary.map_or("-".to_string(), |s| format!("{:.2} ms", s.avg))
+            };
+            self.stats_text = format!(
+                "{:.1} fps\nframe {}\ncpu   {}\ngpu   {}",
+                self.stats.fps(),
+                ms(self.stats.rolling_frame()),
+                ms(self.stats.rolling_cpu()),
+                ms(self.stats.rolling_gpu()),
+            );
+        }
+
+        // Drop shadow keeps the text readable on light colors
+        let shadow = STATS_POSITION + Vec2::ONE;
+        self.text.draw(&self.stats_text, shadow, 1.0, Color::BLACK);
+        self.text
+            .draw(&self.stats_text, STATS_POSITION, 1.0, Color::WHITE);
+    }
+
+    fn cycle_color(&mut self, step: usize) {
+        self.color_index = (self.color_index + step) % COLOR_CYCLE.len();
+        self.renderer.set_color(COLOR_CYCLE[self.color_index]);
+    }
+
+    // Switches between windowed mode and fullscreen on the primary monitor
+    pub fn toggle_fullscreen(&mut self) {
+        let fullscreen = self
+            .window
+            .with_window_mode(|mode| matches!(mode, glfw::WindowMode::FullScreen(_)));
+        if fullscreen {
+            let (x, y, width, height) = self.windowed_rect;
+            self.window.set_monitor(
+                glfw::WindowMode::Windowed,
+                x,
+                y,
+                width as u32,
+                height as u32,
+                None,
+            );
+            return;
+        }
+
+        let (x, y) = self.window.get_pos();
+        let (width, height) = self.window.get_size();
+        self.windowed_rect = (x, y, width, height);
+        let window = &mut self.window;
+        self.glfw.with_primary_monitor(|_, monitor| {
+            let Some(monitor) = monitor else {
+                return;
+            };
+            if let Some(mode) = monitor.get_video_mode() {
+                window.set_monitor(
+                    glfw::WindowMode::FullScreen(monitor),
+                    0,
+                    0,
+                    mode.width,
+                    mode.height,
+                    Some(mode.refresh_rate),
+                );
+            }
+        });
+    }
+
+    // Runs the render loop until the window is closed, then reports the frame
+    // statistics if asked to
+    pub fn run(&mut self) -> Result<(), Error> {
+        println!("OpenGL version: {}", gl_get_string(gl::VERSION));
+        println!(
+            "GLSL version: {}",
+            gl_get_string(gl::SHADING_LANGUAGE_VERSION)
+        );
+
+        // Don't count the startup time as the first frame
+        self.timestep.reset();
+        while !self.window.should_close() {
+            let frame_start = Instant::now();
+
+            // Events of this frame are handled before anything is updated
+            self.glfw.poll_events();
+            let events: Vec<_> = glfw::flush_messages(&self.events).collect();
+            for (_, event) in events {
+                self.handle_event(event);
+            }
+
+            self.renderer.hot_reload();
+            if let Some(target) = &mut self.scene_target {
+                target.post.hot_reload();
+            }
+            let dt = self.timestep.step().as_secs_f32();
+            for _ in 0..self.timestep.advance() {
+                self.renderer.update(dt);
+            }
+            let (width, height) = self.window.get_framebuffer_size();
+            self.gpu_timer.begin(self.stats.len());
+            if let Some(target) = &self.scene_target {
+                target.framebuffer.bind();
+            }
+            self.renderer.render(self.timestep.alpha());
+            if let Some(target) = &self.scene_target {
+                target.present(width, height, self.renderer.time());
+            }
+            self.gpu_timer.end();
+            // Drawn at the window resolution, whatever the scene's is
+            if self.show_stats {
+                self.show_stats();
+            }
+            self.text.flush(width, height);
+            self.capture_frame(width, height);
+            let cpu_time = frame_start.elapsed();
+
+            self.window.swap_buffers();
+            self.limiter.wait();
+
+            self.stats.record(frame_start.elapsed(), cpu_time);
+            for (frame, gpu_time) in self.gpu_timer.poll() {
+                self.stats.set_gpu_time(frame, gpu_time);
+            }
+        }
+
+        self.stop_recording()?;
+        if self.stats_summary {
+            println!("{}", self.stats.summary());
+        }
+        if let Some(path) = &self.stats_csv {
+            self.stats.write_csv(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn swap_interval(vsync: bool) -> glfw::SwapInterval {
+    if vsync {
+        glfw::SwapInterval::Sync(1)
+    } else {
+        glfw::SwapInterval::None
+    }
+}
@@ -0,0 +1,35 @@
+mod opengl;
+mod software;
+
+pub use opengl::{GlBackend, GlMesh};
+pub use software::{SoftwareBackend, SoftwareMesh, SoftwareProgram};
+
+use crate::{Color, ColorVertex, Error};
+
+// The operations a frame is built from. `GlBackend` goes through the `gl`
+// functions of the current context, `SoftwareBackend` rasterizes on the CPU.
+pub trait RenderBackend {
+    type Mesh;
+    type Program;
+
+    fn clear(&mut self, color: &Color);
+    fn upload_mesh(&mut self, vertices: &[ColorVertex]) -> Self::Mesh;
+    // Program that fills the mesh with a single color
+    fn flat_program(&mut self, color: Color) -> Result<Self::Program, Error>;
+    // Program that interpolates the vertex colors across each triangle
+    fn gradient_program(&mut self) -> Result<Self::Program, Error>;
+    fn use_program(&mut self, program: &Self::Program);
+    fn draw(&mut self, mesh: &Self::Mesh);
+}
+
+// Clears to `background` and draws `mesh` with `program`
+pub fn render_frame<B: RenderBackend>(
+    backend: &mut B,
+    background: &Color,
+    program: &B::Program,
+    mesh: &B::Mesh,
+) {
+    backend.clear(background);
+    backend.use_program(program);
+    backend.draw(mesh);
+}
@@ -0,0 +1,86 @@
+use crate::{
+    Buffer, BufferTarget, BufferUsage, Color, ColorVertex, Error, ShaderProgram, VertexArray,
+    gl_clear_color,
+};
+
+use crate::math::Mat4;
+use crate::shader::{
+    DEFAULT_FRAG_SHADER, DEFAULT_VERT_SHADER, GRADIENT_FRAG_SHADER, GRADIENT_VERT_SHADER, U_COLOR,
+    U_MVP,
+};
+
+use super::RenderBackend;
+
+pub struct GlMesh {
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer<ColorVertex>,
+}
+
+impl GlMesh {
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vertex_array
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<ColorVertex> {
+        &self.vertex_buffer
+    }
+
+    // Replaces the vertices, the VAO keeps pointing at the same buffer
+    pub fn set_vertices(&mut self, vertices: &[ColorVertex]) {
+        self.vertex_buffer.set_data(vertices);
+        self.vertex_buffer.unbind();
+    }
+}
+
+// Draws into whatever framebuffer is bound in the current GL context
+#[derive(Debug, Default)]
+pub struct GlBackend;
+
+impl GlBackend {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl RenderBackend for GlBackend {
+    type Mesh = GlMesh;
+    type Program = ShaderProgram;
+
+    fn clear(&mut self, color: &Color) {
+        gl_clear_color(*color);
+        unsafe { gl::Clear(gl::COLOR_BUFFER_BIT) };
+    }
+
+    fn upload_mesh(&mut self, vertices: &[ColorVertex]) -> GlMesh {
+        let vertex_buffer = Buffer::new(BufferTarget::Array, BufferUsage::Dynamic, vertices);
+        let vertex_array = VertexArray::new();
+        vertex_array.set_vertex_buffer(&vertex_buffer);
+        GlMesh {
+            vertex_array,
+            vertex_buffer,
+        }
+    }
+
+    fn flat_program(&mut self, color: Color) -> Result<ShaderProgram, Error> {
+        let program = ShaderProgram::from_sources(DEFAULT_VERT_SHADER, DEFAULT_FRAG_SHADER)?;
+        program.set_uniform(U_COLOR, &color);
+        program.set_uniform(U_MVP, &Mat4::IDENTITY);
+        Ok(program)
+    }
+
+    fn gradient_program(&mut self) -> Result<ShaderProgram, Error> {
+        let program = ShaderProgram::from_sources(GRADIENT_VERT_SHADER, GRADIENT_FRAG_SHADER)?;
+        program.set_uniform(U_MVP, &Mat4::IDENTITY);
+        Ok(program)
+    }
+
+    fn use_program(&mut self, program: &ShaderProgram) {
+        program.bind();
+    }
+
+    fn draw(&mut self, mesh: &GlMesh) {
+        mesh.vertex_array.bind();
+        unsafe { gl::DrawArrays(gl::TRIANGLES, 0, mesh.vertex_buffer.len() as i32) };
+        mesh.vertex_array.unbind();
+    }
+}
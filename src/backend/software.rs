@@ -0,0 +1,183 @@
+use std::path::Path;
+
+use crate::{Color, ColorVertex, Error, capture};
+
+use super::RenderBackend;
+
+pub struct SoftwareMesh {
+    vertices: Vec<ColorVertex>,
+}
+
+impl SoftwareMesh {
+    pub fn vertices(&self) -> &[ColorVertex] {
+        &self.vertices
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftwareProgram {
+    Flat([u8; 4]),
+    // Interpolates the vertex colors
+    Gradient,
+}
+
+// A vertex in pixel coordinates
+#[derive(Clone, Copy)]
+struct ScreenVertex {
+    position: (f32, f32),
+    color: [f32; 4],
+}
+
+// CPU rasterizer drawing filled triangles into an RGBA8 buffer. Follows the
+// GL conventions closely enough to compare frames with the OpenGL backend:
+// pixel centers are sampled, shared edges use a top-left rule and colors are
+// rounded to the nearest 8-bit value.
+pub struct SoftwareBackend {
+    width: u32,
+    height: u32,
+    // Top row first, same as `capture::read_pixels`
+    pixels: Vec<u8>,
+    program: SoftwareProgram,
+}
+
+impl SoftwareBackend {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            program: SoftwareProgram::Flat([255; 4]),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn save_png(&self, path: &Path) -> Result<(), Error> {
+        capture::save_png(path, self.width, self.height, &self.pixels)
+    }
+
+    // Clip space to pixel coordinates, y pointing down
+    fn to_screen(&self, v: &ColorVertex) -> ScreenVertex {
+        let [x, y, _] = v.position;
+        ScreenVertex {
+            position: (
+                (x * 0.5 + 0.5) * self.width as f32,
+                (0.5 - y * 0.5) * self.height as f32,
+            ),
+            color: v.color,
+        }
+    }
+
+    fn fill_triangle(&mut self, a: ScreenVertex, b: ScreenVertex, c: ScreenVertex) {
+        let area = edge(a.position, b.position, c.position);
+        if area == 0.0 {
+            return;
+        }
+        // Keep a consistent winding so the inside is always positive
+        let (b, c, area) = if area < 0.0 {
+            (c, b, -area)
+        } else {
+            (b, c, area)
+        };
+        let colors = [a.color, b.color, c.color];
+        let (a, b, c) = (a.position, b.position, c.position);
+
+        let min_x = a.0.min(b.0).min(c.0).floor().max(0.0) as u32;
+        let min_y = a.1.min(b.1).min(c.1).floor().max(0.0) as u32;
+        let max_x = (a.0.max(b.0).max(c.0).ceil() as u32).min(self.width);
+        let max_y = (a.1.max(b.1).max(c.1).ceil() as u32).min(self.height);
+
+        let edges = [(b, c), (c, a), (a, b)];
+        let top_left = edges.map(|(from, to)| is_top_left(from, to));
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let p = (x as f32 + 0.5, y as f32 + 0.5);
+                // Weights of a, b and c, twice the area of the opposite sub-triangle
+                let weights = edges.map(|(from, to)| edge(from, to, p));
+                let inside = weights
+                    .iter()
+                    .zip(top_left)
+                    .all(|(&w, top_left)| w > 0.0 || (w == 0.0 && top_left));
+                if !inside {
+                    continue;
+                }
+
+                let color = match self.program {
+                    SoftwareProgram::Flat(color) => color,
+                    SoftwareProgram::Gradient => interpolate(colors, weights, area),
+                };
+                let i = (y as usize * self.width as usize + x as usize) * 4;
+                self.pixels[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+// Twice the signed area of the triangle (a, b, p)
+fn edge(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+// Barycentric blend of the vertex colors, like GL varyings without perspective
+fn interpolate(colors: [[f32; 4]; 3], weights: [f32; 3], area: f32) -> [u8; 4] {
+    let channel = |i: usize| {
+        colors
+            .iter()
+            .zip(weights)
+            .map(|(color, w)| color[i] * w)
+            .sum::<f32>()
+            / area
+    };
+    Color::new(channel(0), channel(1), channel(2), channel(3)).to_rgba8()
+}
+
+// For the winding used in `fill_triangle`, with y pointing down
+fn is_top_left(from: (f32, f32), to: (f32, f32)) -> bool {
+    let top = from.1 == to.1 && to.0 > from.0;
+    let left = to.1 < from.1;
+    top || left
+}
+
+impl RenderBackend for SoftwareBackend {
+    type Mesh = SoftwareMesh;
+    type Program = SoftwareProgram;
+
+    fn clear(&mut self, color: &Color) {
+        let color = color.to_rgba8();
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    fn upload_mesh(&mut self, vertices: &[ColorVertex]) -> SoftwareMesh {
+        SoftwareMesh {
+            vertices: vertices.to_vec(),
+        }
+    }
+
+    fn flat_program(&mut self, color: Color) -> Result<SoftwareProgram, Error> {
+        Ok(SoftwareProgram::Flat(color.to_rgba8()))
+    }
+
+    fn gradient_program(&mut self) -> Result<SoftwareProgram, Error> {
+        Ok(SoftwareProgram::Gradient)
+    }
+
+    fn use_program(&mut self, program: &SoftwareProgram) {
+        self.program = *program;
+    }
+
+    fn draw(&mut self, mesh: &SoftwareMesh) {
+        for triangle in mesh.vertices.chunks_exact(3) {
+            let [a, b, c] = [&triangle[0], &triangle[1], &triangle[2]].map(|v| self.to_screen(v));
+            self.fill_triangle(a, b, c);
+        }
+    }
+}
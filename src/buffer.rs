@@ -0,0 +1,306 @@
+use std::marker::PhantomData;
+
+use crate::Color;
+
+// Vertex attributes and the shader `location` each one is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Position,
+    Color,
+    Normal,
+    Uv,
+}
+
+impl Attribute {
+    pub fn location(self) -> u32 {
+        match self {
+            Attribute::Position => 0,
+            Attribute::Color => 1,
+            Attribute::Normal => 2,
+            Attribute::Uv => 3,
+        }
+    }
+
+    // Number of f32 components
+    pub fn components(self) -> usize {
+        match self {
+            Attribute::Position | Attribute::Normal => 3,
+            Attribute::Color => 4,
+            Attribute::Uv => 2,
+        }
+    }
+
+    pub fn size(self) -> usize {
+        self.components() * std::mem::size_of::<f32>()
+    }
+}
+
+// Interleaved attribute layout of a vertex struct. Offsets follow the order
+// attributes are added in, which matches the field order of a `#[repr(C)]`
+// struct made of `f32` arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VertexLayout {
+    attributes: Vec<(Attribute, usize)>,
+    stride: usize,
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, attribute: Attribute) -> Self {
+        self.attributes.push((attribute, self.stride));
+        self.stride += attribute.size();
+        self
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    // Attributes with their byte offset into the vertex
+    pub fn attributes(&self) -> &[(Attribute, usize)] {
+        &self.attributes
+    }
+
+    // Sets up the attribute pointers for the bound VAO and ARRAY_BUFFER
+    fn apply(&self) {
+        for &(attribute, offset) in &self.attributes {
+            unsafe {
+                gl::VertexAttribPointer(
+                    attribute.location(),
+                    attribute.components() as i32,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    self.stride as i32,
+                    offset as *const _,
+                );
+                gl::EnableVertexAttribArray(attribute.location());
+            }
+        }
+    }
+}
+
+// A `#[repr(C)]` vertex struct that can be uploaded to a `Buffer`
+pub trait Vertex: Copy {
+    fn layout() -> VertexLayout;
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionVertex {
+    pub position: [f32; 3],
+}
+
+impl PositionVertex {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self {
+            position: [x, y, z],
+        }
+    }
+}
+
+impl Vertex for PositionVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new().with(Attribute::Position)
+    }
+}
+
+// Position with an RGBA color that is interpolated across the triangle
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl ColorVertex {
+    pub fn new(position: [f32; 3], color: Color) -> Self {
+        Self {
+            position,
+            color: color.to_array(),
+        }
+    }
+}
+
+impl Vertex for ColorVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .with(Attribute::Position)
+            .with(Attribute::Color)
+    }
+}
+
+// Integer type of an index buffer
+pub trait Index: Copy {
+    const GL_TYPE: gl::types::GLenum;
+}
+
+impl Index for u16 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl Index for u32 {
+    const GL_TYPE: gl::types::GLenum = gl::UNSIGNED_INT;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferTarget {
+    Array,
+    ElementArray,
+}
+
+impl BufferTarget {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            BufferTarget::Array => gl::ARRAY_BUFFER,
+            BufferTarget::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferUsage {
+    Static,
+    Dynamic,
+    Stream,
+}
+
+impl BufferUsage {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            BufferUsage::Static => gl::STATIC_DRAW,
+            BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+            BufferUsage::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+// GL buffer object holding a slice of `T`. Deleted on Drop.
+pub struct Buffer<T> {
+    id: u32,
+    target: BufferTarget,
+    usage: BufferUsage,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> Buffer<T> {
+    pub fn new(target: BufferTarget, usage: BufferUsage, data: &[T]) -> Self {
+        let mut id = 0;
+        unsafe { gl::GenBuffers(1, &mut id) };
+
+        let mut buffer = Self {
+            id,
+            target,
+            usage,
+            len: 0,
+            _marker: PhantomData,
+        };
+        buffer.set_data(data);
+        buffer
+    }
+
+    // Replaces the whole buffer contents. Leaves the buffer bound.
+    pub fn set_data(&mut self, data: &[T]) {
+        self.bind();
+        unsafe {
+            gl::BufferData(
+                self.target.gl_enum(),
+                std::mem::size_of_val(data) as isize,
+                data.as_ptr().cast(),
+                self.usage.gl_enum(),
+            );
+        }
+        self.len = data.len();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), self.id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindBuffer(self.target.gl_enum(), 0) }
+    }
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
+
+// GL vertex array object. Deleted on Drop.
+pub struct VertexArray {
+    id: u32,
+}
+
+impl VertexArray {
+    pub fn new() -> Self {
+        let mut id = 0;
+        unsafe { gl::GenVertexArrays(1, &mut id) };
+        Self { id }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindVertexArray(self.id) }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindVertexArray(0) }
+    }
+
+    // Points the attributes of `V` at `buffer`
+    pub fn set_vertex_buffer<V: Vertex>(&self, buffer: &Buffer<V>) {
+        let layout = V::layout();
+        debug_assert_eq!(
+            layout.stride(),
+            std::mem::size_of::<V>(),
+            "vertex layout does not match the size of the vertex struct"
+        );
+
+        self.bind();
+        buffer.bind();
+        layout.apply();
+        buffer.unbind();
+        self.unbind();
+    }
+
+    // Makes `buffer` the index buffer of the VAO. The binding is part of the
+    // VAO state, so the buffer stays bound to it.
+    pub fn set_index_buffer<I: Index>(&self, buffer: &Buffer<I>) {
+        debug_assert_eq!(buffer.target, BufferTarget::ElementArray);
+        self.bind();
+        buffer.bind();
+        self.unbind();
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteVertexArrays(1, &self.id) }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::math::{Mat4, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    // `height` world units fit the viewport vertically, the visible width
+    // follows the aspect ratio
+    Orthographic { height: f32 },
+    // Vertical field of view in radians
+    Perspective { fov_y: f32 },
+}
+
+// Eye looking from `position` at `target`. `aspect_ratio` is kept up to date
+// by `Renderer::resize`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub projection: Projection,
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub aspect_ratio: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    // Looks down -Z at the origin from z = 1
+    pub fn orthographic(height: f32) -> Self {
+        Self {
+            projection: Projection::Orthographic { height },
+            position: Vec3::Z,
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            aspect_ratio: 1.0,
+            near: 0.01,
+            far: 100.0,
+        }
+    }
+
+    // Looks down -Z at the origin from z = 3
+    pub fn perspective(fov_y: f32) -> Self {
+        Self {
+            projection: Projection::Perspective { fov_y },
+            position: Vec3::Z * 3.0,
+            ..Self::orthographic(2.0)
+        }
+    }
+
+    pub fn with_position(self, position: Vec3) -> Self {
+        Self { position, ..self }
+    }
+
+    pub fn with_target(self, target: Vec3) -> Self {
+        Self { target, ..self }
+    }
+
+    pub fn with_clip_planes(self, near: f32, far: f32) -> Self {
+        Self { near, far, ..self }
+    }
+
+    // Keeps the view direction and moves back from the center of the box
+    // between `min` and `max` until it fits vertically. The clip planes are
+    // fitted to the box too.
+    pub fn framing(self, min: Vec3, max: Vec3) -> Self {
+        let center = (min + max) * 0.5;
+        let radius = ((max - min).length() * 0.5).max(f32::EPSILON);
+        let (projection, distance) = match self.projection {
+            Projection::Orthographic { .. } => (
+                Projection::Orthographic {
+                    height: radius * 2.0,
+                },
+                radius * 2.0,
+            ),
+            Projection::Perspective { fov_y } => (self.projection, radius / (fov_y * 0.5).sin()),
+        };
+        let direction = (self.position - self.target).normalize();
+        Self {
+            projection,
+            position: center + direction * distance,
+            target: center,
+            near: distance * 0.01,
+            far: distance + radius * 2.0,
+            ..self
+        }
+    }
+
+    pub fn set_aspect_ratio(&mut self, width: i32, height: i32) {
+        if width > 0 && height > 0 {
+            self.aspect_ratio = width as f32 / height as f32;
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at(self.position, self.target, self.up)
+    }
+
+    pub fn projection_matrix(&self) -> Mat4 {
+        match self.projection {
+            Projection::Orthographic { height } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * self.aspect_ratio;
+                Mat4::orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.near,
+                    self.far,
+                )
+            }
+            Projection::Perspective { fov_y } => {
+                Mat4::perspective(fov_y, self.aspect_ratio, self.near, self.far)
+            }
+        }
+    }
+
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view()
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::orthographic(2.0)
+    }
+}
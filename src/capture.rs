@@ -0,0 +1,362 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::Error;
+
+// Reads the RGBA pixels of the bound read framebuffer, top row first
+pub fn read_pixels(width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![0_u8; width as usize * height as usize * 4];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr().cast(),
+        );
+    }
+    // GL rows start at the bottom of the image
+    flip_rows(&mut pixels, width as usize * 4);
+    pixels
+}
+
+pub fn flip_rows(pixels: &mut [u8], row_len: usize) {
+    let rows = pixels.len() / row_len;
+    for y in 0..rows / 2 {
+        let (top, bottom) = pixels.split_at_mut((rows - 1 - y) * row_len);
+        top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+    }
+}
+
+// Writes 8-bit RGBA pixels, top row first, to a PNG file
+pub fn save_png(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<(), Error> {
+    let file = File::create(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+// UTC time as "20240131-235959-123", sorts chronologically in file names
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time_of_day = seconds % 86_400;
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}-{:03}",
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+// Days since 1970-01-01 to (year, month, day), Howard Hinnant's algorithm
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// "<dir>/screenshot-<timestamp>.png" for the current time
+pub fn screenshot_path(dir: &Path) -> PathBuf {
+    dir.join(format!("screenshot-{}.png", timestamp(SystemTime::now())))
+}
+
+enum RecordTarget {
+    // frame-000000.png, frame-000001.png, ... in a directory
+    Png(PathBuf),
+    // Uncompressed 4:4:4 YUV frames, readable by ffmpeg and most players
+    Y4m {
+        writer: BufWriter<File>,
+        width: u32,
+        height: u32,
+    },
+}
+
+// Writes a sequence of frames to numbered PNGs or a Y4M stream, e.g. to make
+// a video with `ffmpeg -i frame-%06d.png` or `ffmpeg -i frames.y4m`
+pub struct FrameRecorder {
+    path: PathBuf,
+    target: RecordTarget,
+    fps: u32,
+    frames: u32,
+    started: Instant,
+}
+
+impl FrameRecorder {
+    // A path ending in ".y4m" records a Y4M stream, anything else is a
+    // directory for PNG frames, created if needed. `fps` is the frame rate
+    // written to Y4M headers and kept by `record_realtime`.
+    pub fn new(path: &Path, fps: u32) -> Result<Self, Error> {
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let is_y4m = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("y4m"));
+        let target = if is_y4m {
+            let file = File::create(path).map_err(io_error)?;
+            RecordTarget::Y4m {
+                writer: BufWriter::new(file),
+                // Taken from the first frame
+                width: 0,
+                height: 0,
+            }
+        } else {
+            std::fs::create_dir_all(path).map_err(io_error)?;
+            RecordTarget::Png(path.to_path_buf())
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            target,
+            fps: fps.max(1),
+            frames: 0,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Frames recorded so far
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    // Appends 8-bit RGBA pixels, top row first. Y4M streams can't change
+    // size, their frames must all be as large as the first.
+    pub fn record(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<(), Error> {
+        let io_error = |source| Error::Io {
+            path: self.path.clone(),
+            source,
+        };
+        match &mut self.target {
+            RecordTarget::Png(dir) => {
+                let path = dir.join(format!("frame-{:06}.png", self.frames));
+                save_png(&path, width, height, pixels)?;
+            }
+            RecordTarget::Y4m {
+                writer,
+                width: stream_width,
+                height: stream_height,
+            } => {
+                if self.frames == 0 {
+                    (*stream_width, *stream_height) = (width, height);
+                    writeln!(
+                        writer,
+                        "YUV4MPEG2 W{width} H{height} F{}:1 Ip A1:1 C444",
+                        self.fps
+                    )
+                    .map_err(io_error)?;
+                } else if (width, height) != (*stream_width, *stream_height) {
+                    return Err(Error::Capture(format!(
+                        "{}: frame size changed from {stream_width}x{stream_height} to \
+                         {width}x{height}",
+                        self.path.display()
+                    )));
+                }
+                writer.write_all(b"FRAME\n").map_err(io_error)?;
+                writer
+                    .write_all(&rgba_to_yuv444(pixels))
+                    .map_err(io_error)?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    // Records a frame shown `elapsed` after the start of the recording so
+    // that it plays back at real speed: written as often as needed to fill
+    // the time since the previous frame at `fps`, or dropped when that time
+    // is already covered. Returns how many copies were written.
+    pub fn record_at(
+        &mut self,
+        elapsed: Duration,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<u32, Error> {
+        let due = (elapsed.as_secs_f64() * f64::from(self.fps)) as u32 + 1;
+        let copies = due.saturating_sub(self.frames);
+        for _ in 0..copies {
+            self.record(width, height, pixels)?;
+        }
+        Ok(copies)
+    }
+
+    // `record_at` with the time since the recorder was created
+    pub fn record_realtime(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    ) -> Result<u32, Error> {
+        self.record_at(self.started.elapsed(), width, height, pixels)
+    }
+
+    // Flushes buffered output. Also done on drop, but errors are lost there.
+    pub fn finish(mut self) -> Result<(), Error> {
+        if let RecordTarget::Y4m { writer, .. } = &mut self.target {
+            writer.flush().map_err(|source| Error::Io {
+                path: self.path.clone(),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// Planar Y, U and V of RGBA pixels, BT.601 limited range
+fn rgba_to_yuv444(pixels: &[u8]) -> Vec<u8> {
+    let len = pixels.len() / 4;
+    let mut planes = vec![0; len * 3];
+    for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+        let (r, g, b) = (
+            i32::from(pixel[0]),
+            i32::from(pixel[1]),
+            i32::from(pixel[2]),
+        );
+        planes[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+        planes[len + i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+        planes[2 * len + i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+    }
+    planes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Empty directory under the system temp directory, unique per test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rust-triangle-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn load_png(path: &Path) -> (u32, u32, Vec<u8>) {
+        let decoder = png::Decoder::new(std::io::BufReader::new(File::open(path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info.width, info.height, pixels)
+    }
+
+    #[test]
+    fn yuv_planes() {
+        // Limited range luma, neutral chroma for grays
+        assert_eq!(rgba_to_yuv444(&[255, 255, 255, 255]), [235, 128, 128]);
+        assert_eq!(rgba_to_yuv444(&[0, 0, 0, 255]), [16, 128, 128]);
+        // One plane after the other
+        let white_black = [255, 255, 255, 255, 0, 0, 0, 255];
+        assert_eq!(rgba_to_yuv444(&white_black), [235, 16, 128, 128, 128, 128]);
+    }
+
+    #[test]
+    fn capture_timestamp() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        assert_eq!(timestamp(time), "20231114-221320-123");
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101-000000-000");
+        let path = screenshot_path(Path::new("shots"));
+        assert!(path.starts_with("shots"));
+        assert_eq!(path.extension().unwrap(), "png");
+    }
+
+    #[test]
+    fn record_png_frames() {
+        let dir = temp_dir("png");
+        let mut recorder = FrameRecorder::new(&dir, 30).unwrap();
+        let frames = [[255, 0, 0, 255].repeat(6), [0, 0, 255, 255].repeat(6)];
+        for frame in &frames {
+            recorder.record(3, 2, frame).unwrap();
+        }
+        assert_eq!(recorder.frames(), 2);
+        recorder.finish().unwrap();
+
+        for (i, frame) in frames.iter().enumerate() {
+            let (width, height, pixels) = load_png(&dir.join(format!("frame-{i:06}.png")));
+            assert_eq!((width, height), (3, 2));
+            assert_eq!(&pixels, frame);
+        }
+    }
+
+    #[test]
+    fn record_y4m_stream() {
+        let dir = temp_dir("y4m");
+        let path = dir.join("frames.y4m");
+        let mut recorder = FrameRecorder::new(&path, 30).unwrap();
+        let white = [255; 4 * 4 * 2];
+        let black = [0, 0, 0, 255].repeat(4 * 2);
+        recorder.record(4, 2, &white).unwrap();
+        recorder.record(4, 2, &black).unwrap();
+        assert!(matches!(
+            recorder.record(2, 2, &white[..16]),
+            Err(Error::Capture(_))
+        ));
+        recorder.finish().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        let header = b"YUV4MPEG2 W4 H2 F30:1 Ip A1:1 C444\n";
+        assert!(data.starts_with(header));
+        let frame_len = b"FRAME\n".len() + 4 * 2 * 3;
+        assert_eq!(data.len(), header.len() + 2 * frame_len);
+        let first = &data[header.len()..][..frame_len];
+        let second = &data[header.len() + frame_len..];
+        assert!(first.starts_with(b"FRAME\n") && second.starts_with(b"FRAME\n"));
+        // Limited range luma, neutral chroma
+        assert_eq!(&first[6..14], &[235; 8]);
+        assert_eq!(&second[6..14], &[16; 8]);
+        assert!(first[14..].iter().chain(&second[14..]).all(|&c| c == 128));
+    }
+
+    #[test]
+    fn record_at_fixed_rate() {
+        let dir = temp_dir("fixed-rate");
+        let path = dir.join("frames.y4m");
+        let mut recorder = FrameRecorder::new(&path, 10).unwrap();
+        let pixels = [255; 4 * 2 * 2];
+        let ms = Duration::from_millis;
+        assert_eq!(recorder.record_at(ms(0), 2, 2, &pixels).unwrap(), 1);
+        // Faster than 10 fps, dropped
+        assert_eq!(recorder.record_at(ms(50), 2, 2, &pixels).unwrap(), 0);
+        assert_eq!(recorder.record_at(ms(100), 2, 2, &pixels).unwrap(), 1);
+        // A slow frame fills the time it was shown for
+        assert_eq!(recorder.record_at(ms(420), 2, 2, &pixels).unwrap(), 3);
+        assert_eq!(recorder.frames(), 5);
+        recorder.finish().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        let header = b"YUV4MPEG2 W2 H2 F10:1 Ip A1:1 C444\n";
+        assert!(data.starts_with(header));
+        assert_eq!(
+            data.len(),
+            header.len() + 5 * (b"FRAME\n".len() + 2 * 2 * 3)
+        );
+    }
+}
@@ -0,0 +1,700 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+// RGBA color with components from 0.0 to 1.0. Components are stored as given,
+// the conversions below treat them as sRGB unless noted otherwise.
+//
+// Deserialized from an `[r, g, b, a]` or `[r, g, b]` array, or from any
+// string `Color::from_str` understands.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "ColorValue")]
+pub struct Color {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+    pub const GRAY: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+    pub const RED: Color = Color::new(1.0, 0.0, 0.0, 1.0);
+    pub const LIME: Color = Color::new(0.0, 1.0, 0.0, 1.0);
+    pub const BLUE: Color = Color::new(0.0, 0.0, 1.0, 1.0);
+    pub const YELLOW: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+    pub const CYAN: Color = Color::new(0.0, 1.0, 1.0, 1.0);
+    pub const MAGENTA: Color = Color::new(1.0, 0.0, 1.0, 1.0);
+    // Softer than CSS "orange" (#FFA500)
+    pub const WARM_ORANGE: Color = Color::new(1.0, 0.7, 0.2, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::new(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    // 0xRRGGBBAA
+    pub fn from_hex(rgba: u32) -> Self {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Self::from_rgba8(r, g, b, a)
+    }
+
+    pub fn r(&self) -> f32 {
+        self.r
+    }
+
+    pub fn g(&self) -> f32 {
+        self.g
+    }
+
+    pub fn b(&self) -> f32 {
+        self.b
+    }
+
+    pub fn a(&self) -> f32 {
+        self.a
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    // Rounded to the nearest 8-bit value, the same way GL converts colors.
+    // Scaled in f64 so values like 0.7 round like they do on the GPU
+    // (0.7f32 is slightly below 0.7).
+    pub fn to_rgba8(self) -> [u8; 4] {
+        self.to_array()
+            .map(|c| (f64::from(c.clamp(0.0, 1.0)) * 255.0).round() as u8)
+    }
+
+    // 0xRRGGBBAA
+    pub fn to_hex(self) -> u32 {
+        u32::from_be_bytes(self.to_rgba8())
+    }
+
+    // sRGB encoded to linear light, alpha unchanged
+    pub fn to_linear(self) -> Self {
+        self.map_rgb(srgb_to_linear)
+    }
+
+    // Linear light to sRGB encoded, alpha unchanged
+    pub fn to_srgb(self) -> Self {
+        self.map_rgb(linear_to_srgb)
+    }
+
+    // Hue in degrees [0, 360), saturation and value from 0.0 to 1.0
+    pub fn from_hsv(h: f32, s: f32, v: f32, a: f32) -> Self {
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    // (hue, saturation, value)
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (max, min) = self.max_min();
+        let delta = max - min;
+        let s = if max > 0.0 { delta / max } else { 0.0 };
+        (self.hue(max, delta), s, max)
+    }
+
+    // Hue in degrees [0, 360), saturation and lightness from 0.0 to 1.0
+    pub fn from_hsl(h: f32, s: f32, l: f32, a: f32) -> Self {
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = l - c / 2.0;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    // (hue, saturation, lightness)
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (max, min) = self.max_min();
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+        let s = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        (self.hue(max, delta), s, l)
+    }
+
+    // Linear interpolation of every component, `t` from 0.0 (self) to 1.0 (other)
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+
+    pub fn premultiplied(self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    pub fn unpremultiplied(self) -> Self {
+        if self.a == 0.0 {
+            return Self::TRANSPARENT;
+        }
+        Self::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Self::TRANSPARENT);
+        }
+        CSS_COLORS
+            .iter()
+            .find(|(css_name, _)| *css_name == name)
+            .map(|&(_, rgb)| Self::from_hex(rgb << 8 | 0xFF))
+    }
+
+    fn map_rgb(self, f: fn(f32) -> f32) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b), self.a)
+    }
+
+    fn max_min(&self) -> (f32, f32) {
+        (
+            self.r.max(self.g).max(self.b),
+            self.r.min(self.g).min(self.b),
+        )
+    }
+
+    fn hue(&self, max: f32, delta: f32) -> f32 {
+        if delta == 0.0 {
+            return 0.0;
+        }
+        let h = if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        h * 60.0
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::BLACK
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from([r, g, b]: [f32; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        c.to_array()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:08X}", self.to_hex())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid color: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+// Parses `#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`, CSS color names and the CSS
+// functions `rgb(255, 128, 0)`, `rgba(255, 128, 0, 0.5)` (percentages work
+// too), `hsl(30, 100%, 50%)` and `hsla(...)`
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || ParseColorError(s.to_string());
+
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex).ok_or_else(invalid);
+        }
+
+        if let Some((function, args)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) {
+            let args: Vec<&str> = args.split([',', '/']).map(str::trim).collect();
+            return match (function.trim().to_ascii_lowercase().as_str(), &args[..]) {
+                ("rgb" | "rgba", [r, g, b, rest @ ..]) if rest.len() <= 1 => Ok(Self::new(
+                    parse_channel(r).ok_or_else(invalid)?,
+                    parse_channel(g).ok_or_else(invalid)?,
+                    parse_channel(b).ok_or_else(invalid)?,
+                    parse_alpha(rest).ok_or_else(invalid)?,
+                )),
+                ("hsl" | "hsla", [h, s, l, rest @ ..]) if rest.len() <= 1 => Ok(Self::from_hsl(
+                    h.trim_end_matches("deg").parse().map_err(|_| invalid())?,
+                    parse_percent(s).ok_or_else(invalid)?,
+                    parse_percent(l).ok_or_else(invalid)?,
+                    parse_alpha(rest).ok_or_else(invalid)?,
+                )),
+                _ => Err(invalid()),
+            };
+        }
+
+        Self::from_name(s).ok_or_else(invalid)
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    // Short forms repeat each digit: #F80 == #FF8800
+    let expand = |v: u32| {
+        (0..4).rev().fold(0, |acc, i| {
+            let digit = (v >> (i * 4)) & 0xF;
+            acc << 8 | digit << 4 | digit
+        })
+    };
+    match hex.len() {
+        3 => Some(Color::from_hex(expand(value << 4 | 0xF))),
+        4 => Some(Color::from_hex(expand(value))),
+        6 => Some(Color::from_hex(value << 8 | 0xFF)),
+        8 => Some(Color::from_hex(value)),
+        _ => None,
+    }
+}
+
+// 0-255 or a percentage
+fn parse_channel(s: &str) -> Option<f32> {
+    match s.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f32>().ok().map(|p| p / 100.0),
+        None => s.parse::<f32>().ok().map(|v| v / 255.0),
+    }
+}
+
+fn parse_percent(s: &str) -> Option<f32> {
+    s.strip_suffix('%')?
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .map(|p| p / 100.0)
+}
+
+// 0.0-1.0 or a percentage, opaque when missing
+fn parse_alpha(rest: &[&str]) -> Option<f32> {
+    match rest.first() {
+        None => Some(1.0),
+        Some(a) => match a.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>().ok().map(|p| p / 100.0),
+            None => a.parse().ok(),
+        },
+    }
+}
+
+// What a color can be written as in config files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Rgba([f32; 4]),
+    Rgb([f32; 3]),
+    Text(String),
+}
+
+impl TryFrom<ColorValue> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(value: ColorValue) -> Result<Self, Self::Error> {
+        match value {
+            ColorValue::Rgba(rgba) => Ok(rgba.into()),
+            ColorValue::Rgb(rgb) => Ok(rgb.into()),
+            ColorValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// RGB of a hue with the given chroma, before adding the lightness offset
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+pub fn gl_clear_color(c: Color) {
+    unsafe { gl::ClearColor(c.r, c.g, c.b, c.a) }
+}
+
+// CSS Color Module Level 4 named colors, 0xRRGGBB
+const CSS_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xF0F8FF),
+    ("antiquewhite", 0xFAEBD7),
+    ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4),
+    ("azure", 0xF0FFFF),
+    ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF),
+    ("blueviolet", 0x8A2BE2),
+    ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887),
+    ("cadetblue", 0x5F9EA0),
+    ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E),
+    ("coral", 0xFF7F50),
+    ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC),
+    ("crimson", 0xDC143C),
+    ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B),
+    ("darkcyan", 0x008B8B),
+    ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B),
+    ("darkmagenta", 0x8B008B),
+    ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00),
+    ("darkorchid", 0x9932CC),
+    ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A),
+    ("darkseagreen", 0x8FBC8F),
+    ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F),
+    ("darkslategrey", 0x2F4F4F),
+    ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3),
+    ("deeppink", 0xFF1493),
+    ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222),
+    ("floralwhite", 0xFFFAF0),
+    ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF),
+    ("gainsboro", 0xDCDCDC),
+    ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700),
+    ("goldenrod", 0xDAA520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xADFF2F),
+    ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0),
+    ("hotpink", 0xFF69B4),
+    ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082),
+    ("ivory", 0xFFFFF0),
+    ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA),
+    ("lavenderblush", 0xFFF0F5),
+    ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD),
+    ("lightblue", 0xADD8E6),
+    ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF),
+    ("lightgoldenrodyellow", 0xFAFAD2),
+    ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90),
+    ("lightgrey", 0xD3D3D3),
+    ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A),
+    ("lightseagreen", 0x20B2AA),
+    ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0),
+    ("lime", 0x00FF00),
+    ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6),
+    ("magenta", 0xFF00FF),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA),
+    ("mediumblue", 0x0000CD),
+    ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB),
+    ("mediumseagreen", 0x3CB371),
+    ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A),
+    ("mediumturquoise", 0x48D1CC),
+    ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xF5FFFA),
+    ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5),
+    ("navajowhite", 0xFFDEAD),
+    ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500),
+    ("orangered", 0xFF4500),
+    ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA),
+    ("palegreen", 0x98FB98),
+    ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093),
+    ("papayawhip", 0xFFEFD5),
+    ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F),
+    ("pink", 0xFFC0CB),
+    ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000),
+    ("rosybrown", 0xBC8F8F),
+    ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513),
+    ("salmon", 0xFA8072),
+    ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57),
+    ("seashell", 0xFFF5EE),
+    ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0),
+    ("skyblue", 0x87CEEB),
+    ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F),
+    ("steelblue", 0x4682B4),
+    ("tan", 0xD2B48C),
+    ("teal", 0x008080),
+    ("thistle", 0xD8BFD8),
+    ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0),
+    ("violet", 0xEE82EE),
+    ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF),
+    ("whitesmoke", 0xF5F5F5),
+    ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_close(a: Color, b: Color) {
+        let close = a
+            .to_array()
+            .iter()
+            .zip(b.to_array())
+            .all(|(x, y)| (x - y).abs() < EPSILON);
+        assert!(close, "{a:?} != {b:?}");
+    }
+
+    fn parse(s: &str) -> Color {
+        s.parse().unwrap_or_else(|e| panic!("{s}: {e}"))
+    }
+
+    #[test]
+    fn hex_forms() {
+        assert_eq!(parse("#F80"), Color::from_hex(0xFF8800FF));
+        assert_eq!(parse("#F808"), Color::from_hex(0xFF880088));
+        assert_eq!(parse("#1a2B3c"), Color::from_hex(0x1A2B3CFF));
+        assert_eq!(parse("#1A2B3C4D"), Color::from_hex(0x1A2B3C4D));
+        assert_eq!(parse("  #fff  "), Color::WHITE);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("red"), Color::RED);
+        assert_eq!(parse("RebeccaPurple"), Color::from_hex(0x663399FF));
+        assert_eq!(parse("transparent"), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn css_functions() {
+        assert_eq!(parse("rgb(255, 0, 0)"), Color::RED);
+        assert_close(parse("rgb(100%, 50%, 0%)"), Color::rgb(1.0, 0.5, 0.0));
+        assert_close(
+            parse("rgba(255, 255, 255, 0.25)"),
+            Color::WHITE.with_alpha(0.25),
+        );
+        assert_close(parse("rgb(0, 0, 255 / 50%)"), Color::BLUE.with_alpha(0.5));
+        assert_close(parse("hsl(120, 100%, 50%)"), Color::LIME);
+        assert_close(
+            parse("HSLA(240deg, 100%, 50%, 20%)"),
+            Color::BLUE.with_alpha(0.2),
+        );
+        assert_close(parse("hsl(0, 0%, 50%)"), Color::GRAY);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        for bad in [
+            "",
+            "#",
+            "#12",
+            "#12345",
+            "#GGG",
+            "#+12",
+            "notacolor",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, b, c)",
+            "rgb(1, 2, 3",
+            "hsl(120, 100, 50)",
+            "cmyk(0, 0, 0, 0)",
+        ] {
+            let error = bad.parse::<Color>().expect_err(bad);
+            assert_eq!(error.to_string(), format!("Invalid color: {}", bad.trim()));
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for hex in [0x00000000, 0x12345678, 0xFFFFFFFF, 0xFFB233FF] {
+            assert_eq!(Color::from_hex(hex).to_hex(), hex);
+        }
+        assert_eq!(Color::WARM_ORANGE.to_rgba8(), [255, 178, 51, 255]);
+        assert_eq!(Color::rgb(2.0, -1.0, 0.5).to_rgba8(), [255, 0, 128, 255]);
+        assert_eq!(Color::RED.to_string(), "#FF0000FF");
+    }
+
+    #[test]
+    fn hsv_and_hsl_round_trip() {
+        let colors = [
+            Color::rgb(0.2, 0.4, 0.6),
+            Color::rgb(0.9, 0.1, 0.3),
+            Color::rgb(0.5, 0.8, 0.1),
+            Color::WARM_ORANGE,
+            Color::MAGENTA,
+            Color::GRAY,
+        ];
+        for color in colors {
+            let (h, s, v) = color.to_hsv();
+            assert_close(Color::from_hsv(h, s, v, 1.0), color);
+            let (h, s, l) = color.to_hsl();
+            assert_close(Color::from_hsl(h, s, l, 1.0), color);
+        }
+
+        let (h, s, v) = Color::CYAN.to_hsv();
+        assert!((h - 180.0).abs() < EPSILON && s == 1.0 && v == 1.0);
+        let (h, s, l) = Color::YELLOW.to_hsl();
+        assert!((h - 60.0).abs() < EPSILON && s == 1.0 && l == 0.5);
+        // Hues wrap around
+        assert_close(Color::from_hsv(-120.0, 1.0, 1.0, 1.0), Color::BLUE);
+        assert_close(Color::from_hsl(480.0, 1.0, 0.5, 1.0), Color::LIME);
+    }
+
+    #[test]
+    fn srgb_linear_round_trip() {
+        assert_close(
+            Color::GRAY.to_linear(),
+            Color::rgb(0.21404, 0.21404, 0.21404),
+        );
+        // Below the threshold both curves are linear
+        assert_close(
+            Color::rgb(0.02, 0.0, 1.0).to_linear(),
+            Color::rgb(0.02 / 12.92, 0.0, 1.0),
+        );
+        for i in 0..=20 {
+            let c = i as f32 / 20.0;
+            let color = Color::new(c, 1.0 - c, c * c, 0.3);
+            assert_close(color.to_linear().to_srgb(), color);
+            assert_close(color.to_srgb().to_linear(), color);
+        }
+    }
+
+    #[test]
+    fn lerp_and_premultiply() {
+        let a = Color::new(0.0, 0.2, 1.0, 0.0);
+        let b = Color::new(1.0, 0.4, 0.0, 1.0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_close(a.lerp(b, 0.25), Color::new(0.25, 0.25, 0.75, 0.25));
+
+        let color = Color::new(0.8, 0.4, 0.2, 0.5);
+        assert_close(color.premultiplied(), Color::new(0.4, 0.2, 0.1, 0.5));
+        assert_close(color.premultiplied().unpremultiplied(), color);
+        assert_eq!(
+            Color::RED.with_alpha(0.0).unpremultiplied(),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn deserializes_arrays_and_strings() {
+        #[derive(Deserialize)]
+        struct Colors {
+            colors: Vec<Color>,
+        }
+        let parsed: Colors = toml::from_str(
+            r##"colors = [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.5], "#0F0", "white"]"##,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.colors,
+            [
+                Color::RED,
+                Color::BLUE.with_alpha(0.5),
+                Color::LIME,
+                Color::WHITE
+            ]
+        );
+        assert!(toml::from_str::<Colors>(r#"colors = ["nope"]"#).is_err());
+    }
+}
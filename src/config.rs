@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::{Action, Binding, Color, Effect, Error, Filter, ParseColorError, Wrap};
+
+pub const WINDOW_WIDTH: u32 = 1280;
+pub const WINDOW_HEIGHT: u32 = 640;
+pub const WINDOW_TITLE: &str = "GLFW Triangle";
+// Loaded when no `--config` is given and the file exists
+pub const DEFAULT_CONFIG_FILE: &str = "triangle.toml";
+
+pub const USAGE: &str = "Usage: rust-triangle [OPTIONS]
+
+Options:
+  --config <file>         TOML config file (default: triangle.toml if present),
+                          paths in it are relative to its directory
+  --width <px>            Window width
+  --height <px>           Window height
+  --title <text>          Window title
+  --fullscreen            Fullscreen on the primary monitor
+  --windowed              Windowed mode
+  --vsync / --no-vsync    Sync buffer swaps to the display
+  --samples <n>           MSAA samples, 0 disables it
+  --render-scale <s>      Scene resolution relative to the window, e.g. 0.5
+  --gl-version <x.y>      OpenGL context version
+  --gl-profile <profile>  core, compat or any
+  --clear-color <color>   Background color: r,g,b,a floats, #RRGGBBAA, a CSS
+                          name, rgb(...) or hsl(...)
+  --keep-aspect           Letterbox instead of stretching on resize
+  --update-rate <hz>      Fixed animation updates per second
+  --max-fps <n>           Frame rate cap, 0 for none
+  --spin <deg/s>          Rotate the triangle continuously
+  --gradient              Interpolate the corner colors across the triangle
+  --corner-colors <a;b;c> Corner colors of the gradient, implies --gradient
+  --model <file>          OBJ or glTF model drawn instead of the triangle
+  --scene <file>          TOML scene file of shapes drawn instead of the
+                          triangle or model, hot-reloaded
+  --texture <file>        PNG, JPEG or BMP image shown on a quad, or on the
+                          --model
+  --texture-filter <f>    nearest or linear
+  --texture-wrap <w>      repeat, mirror or clamp
+  --effects <list>        Comma-separated post-processing passes applied in
+                          order: grayscale, invert, blur, sharpen, vignette,
+                          fxaa, tonemap or a fragment shader file
+  --vert <file>           Vertex shader file, hot-reloaded
+  --frag <file>           Fragment shader file, hot-reloaded
+  --log-events            Print every window event
+  --stats                 Show frame statistics in the window
+  --stats-summary         Print frame statistics on exit
+  --stats-csv <file>      Write per-frame timings to a CSV file on exit
+  --capture-dir <dir>     Where screenshots and hotkey recordings are saved
+  --record <path>         Record from the start at the --max-fps cap or the
+                          monitor refresh rate: numbered PNGs in a
+                          directory, or a .y4m video file
+  --font <file>           TTF / OTF font for on-screen text (needs the ttf
+                          feature), the built-in bitmap font otherwise
+  --font-size <px>        Pixel size of the --font glyphs
+  --headless              Render one frame offscreen, no window
+  --software              Use the CPU rasterizer for headless frames, which
+                          draws the triangle only: no --model, --scene,
+                          --texture, --effects, --vert or --frag
+  --output <file>         PNG written in headless mode
+  -h, --help              Print this help
+
+Default controls (rebind in the [bindings] table of the config file):
+  WASD / arrows  move        Z / X       rotate      scroll, + / -  zoom
+  C, clicks      color       R           reset       L              wireframe
+  F / F11        fullscreen  V           vsync       F3             stats
+  F12 / Print    screenshot  F9          record
+  Q / Escape     quit";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlProfile {
+    Core,
+    Compat,
+    Any,
+}
+
+impl FromStr for GlProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "core" => Ok(GlProfile::Core),
+            "compat" => Ok(GlProfile::Compat),
+            "any" => Ok(GlProfile::Any),
+            _ => Err(format!("Unknown GL profile: {s}")),
+        }
+    }
+}
+
+// Settings for `App` and `HeadlessApp`, read from a TOML file and the
+// command line. Every key of the file is optional, e.g.
+//
+//   width = 800
+//   height = 600
+//   vsync = false
+//   samples = 4
+//   render_scale = 0.5
+//   gl_version = [4, 1]
+//   clear_color = "#1E1E1EFF"    # or [0.12, 0.12, 0.12, 1.0], "black", ...
+//   gradient = true
+//   corner_colors = ["red", "lime", "blue"]
+//   texture = "logo.png"
+//   texture_filter = "nearest"
+//   effects = ["fxaa", "vignette", "crt.frag"]
+//
+//   [bindings]                   # replaces the default bindings of an action
+//   quit = ["Escape"]
+//   zoom_in = ["scroll:up", "Equal"]
+//   toggle_wireframe = ["mouse:middle"]
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub width: u32,
+    pub height: u32,
+    pub title: String,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub samples: u32,
+    // Size of the scene relative to the window. Anything but 1.0 draws the
+    // scene into a framebuffer that is stretched over the window.
+    pub render_scale: f32,
+    pub gl_version: (u32, u32),
+    pub gl_profile: GlProfile,
+    pub clear_color: Color,
+    // Keep the width / height ratio of the initial size when resizing
+    pub keep_aspect: bool,
+    // Fixed-timestep animation updates per second, independent of the frame rate
+    pub update_rate: u32,
+    // Frames per second the window is limited to, 0 for no limit
+    pub max_fps: u32,
+    // Degrees per second the triangle turns around Z
+    pub spin: f32,
+    // Draw the triangle with the per-vertex `corner_colors` instead of one
+    // flat color
+    pub gradient: bool,
+    pub corner_colors: [Color; 3],
+    // OBJ or glTF file drawn lit instead of the triangle, framed by a
+    // perspective camera
+    pub model: Option<PathBuf>,
+    // TOML file listing the shapes to draw, see `SceneData`. Replaces the
+    // triangle and the model, and is reloaded when it changes.
+    pub scene: Option<PathBuf>,
+    // Image drawn unlit on the model, or on a quad with the image's aspect
+    // ratio without one
+    pub texture: Option<PathBuf>,
+    pub texture_filter: Filter,
+    pub texture_wrap: Wrap,
+    // Full-screen passes over the frame, in order. Shader file passes are
+    // hot-reloaded.
+    pub effects: Vec<Effect>,
+    // GLSL files replacing the default shaders. They are watched and
+    // reloaded while the app runs.
+    pub vert_shader: Option<PathBuf>,
+    pub frag_shader: Option<PathBuf>,
+    // Inputs of the actions that do not use their default bindings
+    pub bindings: HashMap<Action, Vec<Binding>>,
+    pub log_events: bool,
+    // Frame time statistics: shown while running, printed or written to CSV
+    // on exit
+    pub stats: bool,
+    pub stats_summary: bool,
+    pub stats_csv: Option<PathBuf>,
+    // Screenshots and recordings started by hotkey go here
+    pub capture_dir: PathBuf,
+    // Directory of numbered PNG frames or a .y4m file recorded from startup
+    pub record: Option<PathBuf>,
+    // Font of on-screen text. None uses the built-in 8x13 bitmap font.
+    pub font: Option<PathBuf>,
+    pub font_size: f32,
+    // Render a single frame offscreen into `output` instead of opening a window
+    pub headless: bool,
+    pub output: PathBuf,
+    // Headless frames are drawn by the CPU rasterizer instead of OpenGL
+    pub software: bool,
+}
+
+impl Config {
+    // Relative paths in the file start at its directory, so it works from
+    // any working directory. Command line paths stay relative to that.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: Self = toml::from_str(&text).map_err(|source| Error::Toml {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        config.resolve_paths(path.parent().unwrap_or(Path::new("")));
+        Ok(config)
+    }
+
+    // Paths the file leaves at their defaults are not its own and stay as
+    // they are
+    fn resolve_paths(&mut self, base_dir: &Path) {
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = base_dir.join(&*path);
+            }
+        };
+        let files = [
+            &mut self.model,
+            &mut self.scene,
+            &mut self.texture,
+            &mut self.vert_shader,
+            &mut self.frag_shader,
+            &mut self.stats_csv,
+            &mut self.record,
+            &mut self.font,
+        ];
+        files.into_iter().flatten().for_each(resolve);
+        for effect in &mut self.effects {
+            if let Effect::Shader(path) = effect {
+                resolve(path);
+            }
+        }
+        let defaults = Self::default();
+        if self.capture_dir != defaults.capture_dir {
+            resolve(&mut self.capture_dir);
+        }
+        if self.output != defaults.output {
+            resolve(&mut self.output);
+        }
+    }
+
+    // Loads the `--config` file (or `triangle.toml`) and applies the other
+    // command line options on top of it
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, Error> {
+        let args: Vec<String> = args.into_iter().collect();
+
+        let config_file = match args.iter().position(|arg| arg == "--config") {
+            Some(i) => {
+                Some(PathBuf::from(args.get(i + 1).ok_or_else(|| {
+                    Error::Args("Missing value for --config".to_string())
+                })?))
+            }
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists()),
+        };
+
+        let mut config = match config_file {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_args(args).map_err(Error::Args)?;
+        Ok(config)
+    }
+
+    pub fn apply_args<I: IntoIterator<Item = String>>(&mut self, args: I) -> Result<(), String> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+            match arg.as_str() {
+                // Already loaded by `from_args`
+                "--config" => {
+                    value()?;
+                }
+                "--width" => self.width = parse(&arg, &value()?)?,
+                "--height" => self.height = parse(&arg, &value()?)?,
+                "--title" => self.title = value()?,
+                "--fullscreen" => self.fullscreen = true,
+                "--windowed" => self.fullscreen = false,
+                "--vsync" => self.vsync = true,
+                "--no-vsync" => self.vsync = false,
+                "--samples" => self.samples = parse(&arg, &value()?)?,
+                "--render-scale" => self.render_scale = parse(&arg, &value()?)?,
+                "--gl-version" => self.gl_version = parse_gl_version(&value()?)?,
+                "--gl-profile" => self.gl_profile = value()?.parse()?,
+                "--clear-color" => self.clear_color = parse_color(&value()?)?,
+                "--keep-aspect" => self.keep_aspect = true,
+                "--update-rate" => self.update_rate = parse(&arg, &value()?)?,
+                "--max-fps" => self.max_fps = parse(&arg, &value()?)?,
+                "--spin" => self.spin = parse(&arg, &value()?)?,
+                "--gradient" => self.gradient = true,
+                "--corner-colors" => {
+                    self.corner_colors = parse_corner_colors(&value()?)?;
+                    self.gradient = true;
+                }
+                "--model" => self.model = Some(value()?.into()),
+                "--scene" => self.scene = Some(value()?.into()),
+                "--texture" => self.texture = Some(value()?.into()),
+                "--texture-filter" => self.texture_filter = value()?.parse()?,
+                "--texture-wrap" => self.texture_wrap = value()?.parse()?,
+                "--effects" => self.effects = parse_effects(&value()?)?,
+                "--vert" => self.vert_shader = Some(value()?.into()),
+                "--frag" => self.frag_shader = Some(value()?.into()),
+                "--log-events" => self.log_events = true,
+                "--stats" => self.stats = true,
+                "--stats-summary" => self.stats_summary = true,
+                "--stats-csv" => self.stats_csv = Some(value()?.into()),
+                "--capture-dir" => self.capture_dir = value()?.into(),
+                "--record" => self.record = Some(value()?.into()),
+                "--font" => self.font = Some(value()?.into()),
+                "--font-size" => self.font_size = parse(&arg, &value()?)?,
+                "--headless" => self.headless = true,
+                "--software" => self.software = true,
+                "--output" => self.output = value()?.into(),
+                _ => return Err(format!("Unknown argument: {arg}\n\n{USAGE}")),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {arg}: {value}"))
+}
+
+// "fxaa,vignette" -> [Fxaa, Vignette], empty for none
+fn parse_effects(value: &str) -> Result<Vec<Effect>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+// "3.3" -> (3, 3)
+fn parse_gl_version(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("Invalid GL version: {value}");
+    let (major, minor) = value.split_once('.').ok_or_else(invalid)?;
+    Ok((
+        major.parse().map_err(|_| invalid())?,
+        minor.parse().map_err(|_| invalid())?,
+    ))
+}
+
+// "r,g,b,a" with components from 0.0 to 1.0, or anything `Color::from_str`
+// understands
+fn parse_color(value: &str) -> Result<Color, String> {
+    let floats: Result<Vec<f32>, _> = value.split(',').map(|c| c.trim().parse()).collect();
+    match floats.as_deref() {
+        Ok(&[r, g, b, a]) => Ok(Color::new(r, g, b, a)),
+        Ok(&[r, g, b]) => Ok(Color::rgb(r, g, b)),
+        _ => value.parse().map_err(|e: ParseColorError| e.to_string()),
+    }
+}
+
+// "red;lime;blue", three colors in `parse_color` syntax
+fn parse_corner_colors(value: &str) -> Result<[Color; 3], String> {
+    let colors = value
+        .split(';')
+        .map(parse_color)
+        .collect::<Result<Vec<_>, _>>()?;
+    colors
+        .try_into()
+        .map_err(|_| format!("Expected three colors separated by ';': {value}"))
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            title: WINDOW_TITLE.to_string(),
+            fullscreen: false,
+            vsync: true,
+            samples: 0,
+            render_scale: 1.0,
+            gl_version: (3, 3),
+            gl_profile: GlProfile::Core,
+            clear_color: crate::BACKGROUND_COLOR,
+            keep_aspect: false,
+            update_rate: 60,
+            max_fps: 0,
+            spin: 0.0,
+            gradient: false,
+            corner_colors: crate::CORNER_COLORS,
+            model: None,
+            scene: None,
+            texture: None,
+            texture_filter: Filter::Linear,
+            texture_wrap: Wrap::Repeat,
+            effects: Vec::new(),
+            vert_shader: None,
+            frag_shader: None,
+            bindings: HashMap::new(),
+            log_events: false,
+            stats: false,
+            stats_summary: false,
+            stats_csv: None,
+            capture_dir: PathBuf::from("."),
+            record: None,
+            font: None,
+            font_size: 16.0,
+            headless: false,
+            output: PathBuf::from("frame.png"),
+            software: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn apply(line: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        config.apply_args(args(line))?;
+        Ok(config)
+    }
+
+    // Written under the system temp directory, unique per test
+    fn config_file(name: &str, text: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("rust-triangle-{}-{name}.toml", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn arguments() {
+        let config = apply(
+            "--width 800 --height 600 --no-vsync --samples 4 --gl-version 4.1 \
+             --gl-profile compat --clear-color 0.1,0.2,0.3 --max-fps 30 --scene a.toml \
+             --texture-filter nearest --stats-csv stats.csv --headless",
+        )
+        .unwrap();
+        assert_eq!((config.width, config.height), (800, 600));
+        assert!(!config.vsync && config.headless);
+        assert_eq!(config.samples, 4);
+        assert_eq!(config.gl_version, (4, 1));
+        assert_eq!(config.gl_profile, GlProfile::Compat);
+        assert_eq!(config.clear_color, Color::rgb(0.1, 0.2, 0.3));
+        assert_eq!(config.max_fps, 30);
+        assert_eq!(config.scene, Some(PathBuf::from("a.toml")));
+        assert_eq!(config.texture_filter, Filter::Nearest);
+        assert_eq!(config.stats_csv, Some(PathBuf::from("stats.csv")));
+
+        // Later flags win
+        assert!(!apply("--fullscreen --windowed").unwrap().fullscreen);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("1, 0, 0, 0.5"), Ok(Color::RED.with_alpha(0.5)));
+        assert_eq!(parse_color("#00F"), Ok(Color::BLUE));
+        assert_eq!(parse_color("lime"), Ok(Color::LIME));
+        assert_eq!(parse_color("1,2"), Err("Invalid color: 1,2".to_string()));
+
+        let config = apply("--corner-colors white;#000;0,0,1").unwrap();
+        assert_eq!(
+            config.corner_colors,
+            [Color::WHITE, Color::BLACK, Color::BLUE]
+        );
+        assert!(config.gradient);
+        assert_eq!(
+            parse_corner_colors("red;blue"),
+            Err("Expected three colors separated by ';': red;blue".to_string())
+        );
+        assert_eq!(
+            parse_corner_colors("red;blue;nope"),
+            Err("Invalid color: nope".to_string())
+        );
+    }
+
+    #[test]
+    fn gl_versions() {
+        assert_eq!(parse_gl_version("3.3"), Ok((3, 3)));
+        assert_eq!(parse_gl_version("4.60"), Ok((4, 60)));
+        for bad in ["3", "3.", "three.3", "3.3.1", ""] {
+            assert_eq!(
+                parse_gl_version(bad),
+                Err(format!("Invalid GL version: {bad}"))
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_arguments() {
+        let error = |line: &str| apply(line).err().unwrap();
+        assert_eq!(error("--width"), "Missing value for --width");
+        assert_eq!(error("--width wide"), "Invalid value for --width: wide");
+        assert_eq!(error("--samples -1"), "Invalid value for --samples: -1");
+        assert_eq!(error("--gl-profile es"), "Unknown GL profile: es");
+        assert_eq!(error("--effects blur,glow"), "Unknown effect: glow");
+        assert!(error("--frobnicate").starts_with("Unknown argument: --frobnicate\n\nUsage:"));
+    }
+
+    #[test]
+    fn toml_file_with_argument_overrides() {
+        let path = config_file(
+            "overrides",
+            r#"
+                width = 640
+                height = 480
+                vsync = false
+                clear_color = "navy"
+                gl_version = [4, 5]
+                effects = ["fxaa"]
+
+                [bindings]
+                quit = ["Escape"]
+                toggle_stats = []
+            "#,
+        );
+        let mut line = args("--config");
+        line.push(path.display().to_string());
+        line.extend(args("--width 1024 --vsync"));
+        let config = Config::from_args(line).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Arguments override the file, the rest comes from it or the defaults
+        assert_eq!((config.width, config.height), (1024, 480));
+        assert!(config.vsync);
+        assert_eq!(config.clear_color, Color::from_hex(0x000080FF));
+        assert_eq!(config.gl_version, (4, 5));
+        assert_eq!(config.effects, [Effect::Fxaa]);
+        assert_eq!(config.title, WINDOW_TITLE);
+        assert_eq!(
+            config.bindings[&Action::Quit],
+            [Binding::Key(glfw::Key::Escape)]
+        );
+        assert!(config.bindings[&Action::ToggleStats].is_empty());
+    }
+
+    #[test]
+    fn paths_relative_to_the_file() {
+        let path = config_file(
+            "paths",
+            r#"
+                model = "models/cube.obj"
+                texture = "/images/wood.png"
+                effects = ["invert", "crt.frag"]
+                capture_dir = "shots"
+            "#,
+        );
+        let mut line = args("--config");
+        line.push(path.display().to_string());
+        line.extend(args("--scene scene.toml"));
+        let config = Config::from_args(line).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let dir = std::env::temp_dir();
+        assert_eq!(config.model, Some(dir.join("models/cube.obj")));
+        assert_eq!(config.texture, Some(PathBuf::from("/images/wood.png")));
+        assert_eq!(
+            config.effects,
+            [Effect::Invert, Effect::Shader(dir.join("crt.frag"))]
+        );
+        assert_eq!(config.capture_dir, dir.join("shots"));
+        // Arguments and defaults are relative to the working directory
+        assert_eq!(config.scene, Some(PathBuf::from("scene.toml")));
+        assert_eq!(config.output, PathBuf::from("frame.png"));
+    }
+
+    #[test]
+    fn toml_errors() {
+        let unknown = config_file("unknown", "width = 640\nwidht = 480\n");
+        let error = Config::from_file(&unknown).err().unwrap().to_string();
+        std::fs::remove_file(&unknown).unwrap();
+        assert!(error.contains("unknown field `widht`"), "{error}");
+        assert!(error.contains(&unknown.display().to_string()), "{error}");
+
+        let bad_color = config_file("bad-color", "clear_color = \"#12\"\n");
+        let error = Config::from_file(&bad_color).err().unwrap().to_string();
+        std::fs::remove_file(&bad_color).unwrap();
+        assert!(error.contains("Invalid color: #12"), "{error}");
+
+        let bad_binding = config_file("bad-binding", "[bindings]\nquit = [\"Hyper\"]\n");
+        let error = Config::from_file(&bad_binding).err().unwrap().to_string();
+        std::fs::remove_file(&bad_binding).unwrap();
+        assert!(error.contains("Unknown input binding: Hyper"), "{error}");
+
+        let missing = Config::from_args(args("--config does-not-exist.toml"));
+        assert!(matches!(missing, Err(Error::Io { .. })));
+        let no_value = Config::from_args(args("--config")).err().unwrap();
+        assert_eq!(
+            no_value.to_string(),
+            Error::Args("Missing value for --config".into()).to_string()
+        );
+    }
+}
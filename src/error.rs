@@ -0,0 +1,101 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::ShaderError;
+
+#[derive(Debug)]
+pub enum Error {
+    Glfw(glfw::InitError),
+    WindowCreation,
+    Shader(ShaderError),
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Args(String),
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    Headless(String),
+    Framebuffer(String),
+    Png(png::EncodingError),
+    Capture(String),
+    Font(String),
+    Mesh {
+        path: PathBuf,
+        message: String,
+    },
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    Scene {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Glfw(e) => write!(f, "Failed to initialize GLFW: {e}"),
+            Error::WindowCreation => write!(f, "Failed to create GLFW window."),
+            Error::Shader(e) => write!(f, "{e}"),
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Args(msg) => write!(f, "{msg}"),
+            Error::Toml { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Headless(msg) => write!(f, "Headless rendering: {msg}"),
+            Error::Framebuffer(msg) => write!(f, "Framebuffer: {msg}"),
+            Error::Png(e) => write!(f, "Failed to write PNG: {e}"),
+            Error::Capture(msg) => write!(f, "Capture failed: {msg}"),
+            Error::Font(msg) => write!(f, "Failed to load font: {msg}"),
+            // Mesh data that didn't come from a file has no path
+            Error::Mesh { path, message } if path.as_os_str().is_empty() => {
+                write!(f, "Invalid mesh: {message}")
+            }
+            Error::Mesh { path, message } => write!(f, "{}: {message}", path.display()),
+            Error::Image { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Scene { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Glfw(e) => Some(e),
+            Error::Shader(e) => Some(e),
+            Error::Io { source, .. } => Some(source),
+            Error::Toml { source, .. } => Some(source),
+            Error::Png(e) => Some(e),
+            Error::Image { source, .. } => Some(source),
+            Error::WindowCreation
+            | Error::Args(_)
+            | Error::Headless(_)
+            | Error::Framebuffer(_)
+            | Error::Capture(_)
+            | Error::Font(_)
+            | Error::Mesh { .. }
+            | Error::Scene { .. } => None,
+        }
+    }
+}
+
+impl From<glfw::InitError> for Error {
+    fn from(e: glfw::InitError) -> Self {
+        Error::Glfw(e)
+    }
+}
+
+impl From<ShaderError> for Error {
+    fn from(e: ShaderError) -> Self {
+        Error::Shader(e)
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(e: png::EncodingError) -> Self {
+        Error::Png(e)
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+#[cfg(feature = "ttf")]
+use std::path::Path;
+
+use crate::{Config, Error, Vec2};
+
+// 8x13 glyphs of the X11 misc-fixed font (public domain), printable ASCII
+// from ' ' to '~'. 1 bit per pixel, most significant bit first, 16 glyphs
+// per row of the image.
+const BUILTIN_FONT: &[u8] = include_bytes!("../assets/font_8x13.raw");
+const BUILTIN_GLYPH_SIZE: (u32, u32) = (8, 13);
+const BUILTIN_GLYPHS_PER_ROW: u32 = 16;
+// Drawn for characters the font has no glyph for
+const REPLACEMENT_CHAR: char = '?';
+// Gap between glyphs packed into a TTF atlas, keeps linear filtering from
+// bleeding neighbours in
+#[cfg(feature = "ttf")]
+const ATLAS_PADDING: u32 = 1;
+// Widened to the widest glyph at large sizes
+#[cfg(feature = "ttf")]
+const ATLAS_WIDTH: u32 = 512;
+
+// Where a glyph is in the atlas and how it sits on the line, in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    // Top-left corner and size of the glyph in the atlas
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    // From the pen position at the top of the line to the top-left corner of
+    // the glyph, y down
+    pub offset: Vec2,
+    // How far the pen moves after the glyph
+    pub advance: f32,
+}
+
+// Glyphs rasterized into a single-channel coverage atlas, 0 is transparent
+// and 255 fully covered
+#[derive(Debug, Clone)]
+pub struct Font {
+    atlas: Vec<u8>,
+    atlas_width: u32,
+    atlas_height: u32,
+    glyphs: HashMap<char, Glyph>,
+    line_height: f32,
+    smooth: bool,
+}
+
+impl Font {
+    // The 8x13 bitmap font compiled into the binary
+    pub fn builtin() -> Self {
+        let (glyph_width, glyph_height) = BUILTIN_GLYPH_SIZE;
+        let atlas_width = glyph_width * BUILTIN_GLYPHS_PER_ROW;
+        let row_bytes = atlas_width as usize / 8;
+        let atlas_height = (BUILTIN_FONT.len() / row_bytes) as u32;
+
+        let atlas = BUILTIN_FONT
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| if byte & (0x80 >> bit) != 0 { 255 } else { 0 }))
+            .collect();
+        let glyphs = (' '..='~')
+            .enumerate()
+            .map(|(i, c)| {
+                let i = i as u32;
+                let glyph = Glyph {
+                    x: i % BUILTIN_GLYPHS_PER_ROW * glyph_width,
+                    y: i / BUILTIN_GLYPHS_PER_ROW * glyph_height,
+                    width: glyph_width,
+                    height: glyph_height,
+                    offset: Vec2::ZERO,
+                    advance: glyph_width as f32,
+                };
+                (c, glyph)
+            })
+            .collect();
+
+        Self {
+            atlas,
+            atlas_width,
+            atlas_height,
+            glyphs,
+            line_height: glyph_height as f32,
+            smooth: false,
+        }
+    }
+
+    // The `font` of the config at `font_size`, or the built-in font
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let Some(path) = &config.font else {
+            return Ok(Self::builtin());
+        };
+        #[cfg(feature = "ttf")]
+        return Self::load_ttf(path, config.font_size);
+        #[cfg(not(feature = "ttf"))]
+        Err(Error::Font(format!(
+            "{}: built without the ttf feature",
+            path.display()
+        )))
+    }
+
+    // Rasterizes the printable ASCII range of a TrueType / OpenType font at
+    // `size` pixels per em
+    #[cfg(feature = "ttf")]
+    pub fn from_ttf_bytes(bytes: &[u8], size: f32) -> Result<Self, Error> {
+        let settings = fontdue::FontSettings {
+            scale: size,
+            ..fontdue::FontSettings::default()
+        };
+        let font =
+            fontdue::Font::from_bytes(bytes, settings).map_err(|e| Error::Font(e.to_string()))?;
+        let line_metrics = font
+            .horizontal_line_metrics(size)
+            .ok_or_else(|| Error::Font("font has no horizontal metrics".to_string()))?;
+
+        let rasterized: Vec<_> = (' '..='~').map(|c| (c, font.rasterize(c, size))).collect();
+        let atlas_width = rasterized
+            .iter()
+            .map(|(_, (metrics, _))| metrics.width as u32)
+            .fold(ATLAS_WIDTH, u32::max);
+
+        // Shelf packing: glyphs left to right, a new row when one is full
+        let mut atlas = Vec::new();
+        let mut glyphs = HashMap::new();
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for (c, (metrics, coverage)) in rasterized {
+            let (width, height) = (metrics.width as u32, metrics.height as u32);
+            if x + width > atlas_width {
+                x = 0;
+                y += row_height + ATLAS_PADDING;
+                row_height = 0;
+            }
+            let len = ((y + height) * atlas_width) as usize;
+            if atlas.len() < len {
+                atlas.resize(len, 0);
+            }
+            for row in 0..height {
+                let src = (row * width) as usize;
+                let dst = ((y + row) * atlas_width + x) as usize;
+                atlas[dst..dst + width as usize]
+                    .copy_from_slice(&coverage[src..src + width as usize]);
+            }
+
+            let glyph = Glyph {
+                x,
+                y,
+                width,
+                height,
+                offset: Vec2::new(
+                    metrics.xmin as f32,
+                    line_metrics.ascent - (metrics.ymin + metrics.height as i32) as f32,
+                ),
+                advance: metrics.advance_width,
+            };
+            glyphs.insert(c, glyph);
+            x += width + ATLAS_PADDING;
+            row_height = row_height.max(height);
+        }
+        let atlas_height = (y + row_height).max(1);
+        atlas.resize((atlas_height * atlas_width) as usize, 0);
+
+        Ok(Self {
+            atlas,
+            atlas_width,
+            atlas_height,
+            glyphs,
+            line_height: line_metrics.new_line_size.ceil(),
+            smooth: true,
+        })
+    }
+
+    #[cfg(feature = "ttf")]
+    pub fn load_ttf(path: &Path, size: f32) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_ttf_bytes(&bytes, size)
+    }
+
+    pub fn atlas(&self) -> &[u8] {
+        &self.atlas
+    }
+
+    pub fn atlas_size(&self) -> (u32, u32) {
+        (self.atlas_width, self.atlas_height)
+    }
+
+    // Distance between baselines, in pixels
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    // Whether the atlas looks better filtered linearly than with nearest
+    // sampling. False for pixel fonts.
+    pub fn smooth(&self) -> bool {
+        self.smooth
+    }
+
+    // The glyph of `c`, or of '?' when the font has none
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&REPLACEMENT_CHAR))
+    }
+
+    // Width of the longest line and height of all lines, in pixels at scale 1
+    pub fn measure(&self, text: &str) -> Vec2 {
+        let mut size = Vec2::ZERO;
+        for line in text.lines() {
+            let width: f32 = line
+                .chars()
+                .filter_map(|c| self.glyph(c))
+                .map(|glyph| glyph.advance)
+                .sum();
+            size.x = size.x.max(width);
+            size.y += self.line_height;
+        }
+        size
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
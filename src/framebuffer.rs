@@ -0,0 +1,241 @@
+use crate::{Error, Filter, Texture2D, TextureOptions, Wrap, capture};
+
+// Where the color of a framebuffer goes. Multisampled images can't be
+// sampled as textures, they have to be resolved first.
+enum ColorAttachment {
+    Texture(Texture2D),
+    Multisampled(u32),
+}
+
+// Framebuffer object with an RGBA8 color attachment and a combined 24-bit
+// depth / 8-bit stencil renderbuffer. Without multisampling the color is a
+// texture the scene can be sampled from afterwards. Needs a current GL
+// context, deleted on Drop.
+pub struct Framebuffer {
+    id: u32,
+    color: ColorAttachment,
+    depth_stencil: u32,
+    width: u32,
+    height: u32,
+    samples: u32,
+}
+
+impl Framebuffer {
+    // `samples` > 0 makes a multisampled framebuffer
+    pub fn new(width: u32, height: u32, samples: u32) -> Result<Self, Error> {
+        let mut id = 0;
+        unsafe { gl::GenFramebuffers(1, &mut id) };
+        let (color, depth_stencil) = attach(id, width, height, samples);
+        let framebuffer = Self {
+            id,
+            color,
+            depth_stencil,
+            width,
+            height,
+            samples,
+        };
+        framebuffer.check_status()?;
+        Ok(framebuffer)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    // The color attachment, None when multisampled
+    pub fn color_texture(&self) -> Option<&Texture2D> {
+        match &self.color {
+            ColorAttachment::Texture(texture) => Some(texture),
+            ColorAttachment::Multisampled(_) => None,
+        }
+    }
+
+    // Makes this the target of draws and reads. The viewport is left alone.
+    pub fn bind(&self) {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, self.id) };
+    }
+
+    // Goes back to the default framebuffer
+    pub fn unbind(&self) {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0) };
+    }
+
+    // Replaces the attachments with new ones of the given size. Their
+    // contents are undefined until drawn again.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        if (width, height) == (self.width, self.height) {
+            return Ok(());
+        }
+        self.delete_attachments();
+        (self.color, self.depth_stencil) = attach(self.id, width, height, self.samples);
+        self.width = width;
+        self.height = height;
+        self.check_status()
+    }
+
+    // Copies the color into `target`, which must have the same size. This is
+    // how a multisampled framebuffer is turned into a texture or read back.
+    pub fn resolve(&self, target: &Framebuffer) {
+        debug_assert_eq!(self.size(), target.size(), "resolve target size differs");
+        self.blit(target.id, target.width, target.height, Filter::Nearest);
+    }
+
+    // Stretches the color over a `width` x `height` default framebuffer.
+    // A multisampled framebuffer can only be copied at its own size, resolve
+    // it first to scale it.
+    pub fn blit_to_screen(&self, width: u32, height: u32) {
+        let filter = if (width, height) == self.size() {
+            Filter::Nearest
+        } else {
+            Filter::Linear
+        };
+        self.blit(0, width, height, filter);
+    }
+
+    // RGBA pixels of the color attachment, top row first. Multisampled
+    // framebuffers have to be resolved into a single-sampled one first.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        assert_eq!(self.samples, 0, "resolve multisampled framebuffers first");
+        let mut previous = 0;
+        unsafe {
+            gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+        }
+        let pixels = capture::read_pixels(self.width, self.height);
+        unsafe { gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous as u32) };
+        pixels
+    }
+
+    // Leaves `target` bound
+    fn blit(&self, target: u32, width: u32, height: u32, filter: Filter) {
+        let filter = match filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        };
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target);
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::COLOR_BUFFER_BIT,
+                filter,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, target);
+        }
+    }
+
+    fn check_status(&self) -> Result<(), Error> {
+        let status = unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(Error::Framebuffer(format!(
+                "{}x{} with {} samples is incomplete: 0x{status:X}",
+                self.width, self.height, self.samples
+            )));
+        }
+        Ok(())
+    }
+
+    fn delete_attachments(&self) {
+        unsafe {
+            if let ColorAttachment::Multisampled(renderbuffer) = self.color {
+                gl::DeleteRenderbuffers(1, &renderbuffer);
+            }
+            gl::DeleteRenderbuffers(1, &self.depth_stencil);
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        self.delete_attachments();
+        unsafe { gl::DeleteFramebuffers(1, &self.id) }
+    }
+}
+
+// Creates the attachments of framebuffer `id` and attaches them
+fn attach(id: u32, width: u32, height: u32, samples: u32) -> (ColorAttachment, u32) {
+    let (width, height) = (width as i32, height as i32);
+    let storage = |renderbuffer: u32, format: gl::types::GLenum| unsafe {
+        gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+        if samples > 0 {
+            gl::RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                samples as i32,
+                format,
+                width,
+                height,
+            );
+        } else {
+            gl::RenderbufferStorage(gl::RENDERBUFFER, format, width, height);
+        }
+        gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+    };
+
+    unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, id) };
+    let color = if samples > 0 {
+        let mut renderbuffer = 0;
+        unsafe {
+            gl::GenRenderbuffers(1, &mut renderbuffer);
+            storage(renderbuffer, gl::RGBA8);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                renderbuffer,
+            );
+        }
+        ColorAttachment::Multisampled(renderbuffer)
+    } else {
+        // Usually drawn 1:1 onto the screen or into a post-processing pass
+        let options = TextureOptions {
+            filter: Filter::Linear,
+            wrap: Wrap::Clamp,
+            mipmaps: false,
+        };
+        let texture = Texture2D::empty(width as u32, height as u32, &options);
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture.id(),
+                0,
+            );
+        }
+        ColorAttachment::Texture(texture)
+    };
+
+    let mut depth_stencil = 0;
+    unsafe {
+        gl::GenRenderbuffers(1, &mut depth_stencil);
+        storage(depth_stencil, gl::DEPTH24_STENCIL8);
+        gl::FramebufferRenderbuffer(
+            gl::FRAMEBUFFER,
+            gl::DEPTH_STENCIL_ATTACHMENT,
+            gl::RENDERBUFFER,
+            depth_stencil,
+        );
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+    (color, depth_stencil)
+}
@@ -0,0 +1,269 @@
+use std::path::Path;
+
+use khronos_egl as egl;
+
+use crate::{
+    Config, Error, Font, Framebuffer, GlProfile, PostProcessor, Renderer, TextRenderer, capture,
+};
+
+// EGL_PLATFORM_SURFACELESS_MESA
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+// An initialized EGL display, terminated on Drop
+struct EglDisplay {
+    egl: egl::DynamicInstance<egl::EGL1_4>,
+    display: egl::Display,
+}
+
+impl Drop for EglDisplay {
+    fn drop(&mut self) {
+        let _ = self.egl.terminate(self.display);
+    }
+}
+
+// A GL context of the given version and profile without any window or
+// surface, made current on creation. Uses Mesa's surfaceless platform when
+// available, so it also works with the llvmpipe software rasterizer on
+// machines without a display or GPU.
+pub struct HeadlessContext {
+    // Declared last so the display outlives the context
+    context: egl::Context,
+    display: EglDisplay,
+}
+
+impl HeadlessContext {
+    pub fn new(version: (u32, u32), profile: GlProfile) -> Result<Self, Error> {
+        let egl = unsafe { egl::DynamicInstance::<egl::EGL1_4>::load_required() }
+            .map_err(|e| Error::Headless(format!("Failed to load libEGL: {e}")))?;
+
+        let display = egl
+            .upcast::<egl::EGL1_5>()
+            .and_then(|egl| unsafe {
+                egl.get_platform_display(
+                    PLATFORM_SURFACELESS_MESA,
+                    egl::DEFAULT_DISPLAY,
+                    &[egl::ATTRIB_NONE],
+                )
+                .ok()
+            })
+            .or_else(|| unsafe { egl.get_display(egl::DEFAULT_DISPLAY) })
+            .ok_or_else(|| Error::Headless("No EGL display available".to_string()))?;
+
+        let egl_error = |call: &str, e: egl::Error| Error::Headless(format!("{call} failed: {e}"));
+
+        egl.initialize(display)
+            .map_err(|e| egl_error("eglInitialize", e))?;
+        // Terminates the display on the error paths below
+        let guard = EglDisplay { egl, display };
+        let egl = &guard.egl;
+        egl.bind_api(egl::OPENGL_API)
+            .map_err(|e| egl_error("eglBindAPI", e))?;
+
+        let config = egl
+            .choose_first_config(
+                display,
+                &[
+                    egl::SURFACE_TYPE,
+                    egl::PBUFFER_BIT,
+                    egl::RENDERABLE_TYPE,
+                    egl::OPENGL_BIT,
+                    egl::NONE,
+                ],
+            )
+            .map_err(|e| egl_error("eglChooseConfig", e))?
+            .ok_or_else(|| Error::Headless("No EGL config supports OpenGL".to_string()))?;
+
+        let context = egl
+            .create_context(display, config, None, &context_attributes(version, profile))
+            .map_err(|e| egl_error("eglCreateContext", e))?;
+
+        // Surfaceless, everything is drawn into framebuffer objects
+        if let Err(e) = egl.make_current(display, None, None, Some(context)) {
+            let _ = egl.destroy_context(display, context);
+            return Err(egl_error("eglMakeCurrent", e));
+        }
+
+        // Load GL Lib
+        gl::load_with(|name| {
+            egl.get_proc_address(name)
+                .map_or(std::ptr::null(), |f| f as *const _)
+        });
+
+        Ok(Self {
+            context,
+            display: guard,
+        })
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        let EglDisplay { egl, display } = &self.display;
+        let _ = egl.make_current(*display, None, None, None);
+        let _ = egl.destroy_context(*display, self.context);
+    }
+}
+
+// Same version and profile hints as the window context of `App`
+fn context_attributes((major, minor): (u32, u32), profile: GlProfile) -> Vec<egl::Int> {
+    let mut attributes = vec![
+        egl::CONTEXT_MAJOR_VERSION,
+        major as egl::Int,
+        egl::CONTEXT_MINOR_VERSION,
+        minor as egl::Int,
+    ];
+    let profile_bit = match profile {
+        GlProfile::Core => Some(egl::CONTEXT_OPENGL_CORE_PROFILE_BIT),
+        GlProfile::Compat => Some(egl::CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT),
+        GlProfile::Any => None,
+    };
+    if let Some(bit) = profile_bit {
+        attributes.extend([egl::CONTEXT_OPENGL_PROFILE_MASK, bit]);
+    }
+    attributes.push(egl::NONE);
+    attributes
+}
+
+// Runs the same `Renderer` and effects as `App`, but into an offscreen
+// framebuffer, multisampled if the config asks for samples
+pub struct HeadlessApp {
+    // Declared first so the GL objects go away while the context still exists
+    renderer: Renderer,
+    text: TextRenderer,
+    target: Framebuffer,
+    // Single-sampled copy of a multisampled `target`, the one read back
+    resolved: Option<Framebuffer>,
+    post: PostProcessor,
+    context: HeadlessContext,
+}
+
+impl HeadlessApp {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let context = HeadlessContext::new(config.gl_version, config.gl_profile)?;
+        let target = Framebuffer::new(config.width, config.height, config.samples)?;
+        let resolved = if config.samples > 0 {
+            Some(Framebuffer::new(config.width, config.height, 0)?)
+        } else {
+            None
+        };
+        let post = PostProcessor::new(&config.effects, config.width, config.height)?;
+
+        let mut renderer = Renderer::new(config)?;
+        renderer.resize(config.width as i32, config.height as i32);
+        let text = TextRenderer::new(Font::from_config(config)?)?;
+
+        Ok(Self {
+            renderer,
+            text,
+            target,
+            resolved,
+            post,
+            context,
+        })
+    }
+
+    pub fn context(&self) -> &HeadlessContext {
+        &self.context
+    }
+
+    pub fn renderer(&self) -> &Renderer {
+        &self.renderer
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    // Text queued here is drawn over the next rendered frame
+    pub fn text_mut(&mut self) -> &mut TextRenderer {
+        &mut self.text
+    }
+
+    // The framebuffer frames are drawn into
+    pub fn target(&self) -> &Framebuffer {
+        &self.target
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.target.size()
+    }
+
+    // Changes the size of the following frames, like resizing a window
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        self.target.resize(width, height)?;
+        if let Some(resolved) = &mut self.resolved {
+            resolved.resize(width, height)?;
+        }
+        self.post.resize(width, height)?;
+        self.renderer.resize(width as i32, height as i32);
+        Ok(())
+    }
+
+    pub fn post_processor_mut(&mut self) -> &mut PostProcessor {
+        &mut self.post
+    }
+
+    // Renders one frame and returns its RGBA pixels, top row first. Text is
+    // drawn after the effects.
+    pub fn render(&mut self) -> Vec<u8> {
+        let (width, height) = self.size();
+        self.target.bind();
+        self.renderer.render(1.0);
+        let mut frame = &self.target;
+        if let Some(resolved) = &self.resolved {
+            self.target.resolve(resolved);
+            frame = resolved;
+        }
+        if let Some(texture) = frame.color_texture()
+            && let Some(output) = self.post.apply(texture, self.renderer.time())
+        {
+            frame = output;
+        }
+        frame.bind();
+        self.text.flush(width as i32, height as i32);
+        let pixels = frame.read_pixels();
+        frame.unbind();
+        pixels
+    }
+
+    pub fn render_to_png(&mut self, path: &Path) -> Result<(), Error> {
+        let pixels = self.render();
+        let (width, height) = self.size();
+        capture::save_png(path, width, height, &pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_attributes() {
+        let version = [egl::CONTEXT_MAJOR_VERSION, 3, egl::CONTEXT_MINOR_VERSION, 3];
+        let core = context_attributes((3, 3), GlProfile::Core);
+        assert_eq!(core[..4], version);
+        assert_eq!(
+            core[4..],
+            [
+                egl::CONTEXT_OPENGL_PROFILE_MASK,
+                egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                egl::NONE
+            ]
+        );
+        let compat = context_attributes((3, 3), GlProfile::Compat);
+        assert_eq!(
+            compat[4..],
+            [
+                egl::CONTEXT_OPENGL_PROFILE_MASK,
+                egl::CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT,
+                egl::NONE
+            ]
+        );
+        // No profile mask, the driver's default
+        assert_eq!(context_attributes((3, 3), GlProfile::Any)[4..], [egl::NONE]);
+        assert_eq!(
+            context_attributes((4, 6), GlProfile::Any)[..4],
+            [egl::CONTEXT_MAJOR_VERSION, 4, egl::CONTEXT_MINOR_VERSION, 6]
+        );
+    }
+}
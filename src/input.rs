@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use glfw::{Key, MouseButton, WindowEvent};
+use serde::Deserialize;
+
+// Named things the user can do, bound to inputs by an `InputMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    RotateLeft,
+    RotateRight,
+    ZoomIn,
+    ZoomOut,
+    NextColor,
+    PreviousColor,
+    ResetView,
+    ToggleWireframe,
+    ToggleFullscreen,
+    ToggleVsync,
+    ToggleStats,
+    Screenshot,
+    ToggleRecording,
+}
+
+impl Action {
+    pub const ALL: [Action; 18] = [
+        Action::Quit,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::NextColor,
+        Action::PreviousColor,
+        Action::ResetView,
+        Action::ToggleWireframe,
+        Action::ToggleFullscreen,
+        Action::ToggleVsync,
+        Action::ToggleStats,
+        Action::Screenshot,
+        Action::ToggleRecording,
+    ];
+
+    // Name used in config files, e.g. "toggle_wireframe"
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::RotateLeft => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::NextColor => "next_color",
+            Action::PreviousColor => "previous_color",
+            Action::ResetView => "reset_view",
+            Action::ToggleWireframe => "toggle_wireframe",
+            Action::ToggleFullscreen => "toggle_fullscreen",
+            Action::ToggleVsync => "toggle_vsync",
+            Action::ToggleStats => "toggle_stats",
+            Action::Screenshot => "screenshot",
+            Action::ToggleRecording => "toggle_recording",
+        }
+    }
+
+    // Whether holding a key keeps firing the action. Only the continuous
+    // ones do, toggles and one-shot actions fire once per press.
+    pub fn repeats(self) -> bool {
+        matches!(
+            self,
+            Action::MoveLeft
+                | Action::MoveRight
+                | Action::MoveUp
+                | Action::MoveDown
+                | Action::RotateLeft
+                | Action::RotateRight
+                | Action::ZoomIn
+                | Action::ZoomOut
+        )
+    }
+
+    // Bindings used when the config does not mention the action
+    pub fn default_bindings(self) -> Vec<Binding> {
+        use Binding::{Key as K, Mouse, ScrollDown, ScrollUp};
+        match self {
+            Action::Quit => vec![K(Key::Q), K(Key::Escape)],
+            Action::MoveLeft => vec![K(Key::A), K(Key::Left)],
+            Action::MoveRight => vec![K(Key::D), K(Key::Right)],
+            Action::MoveUp => vec![K(Key::W), K(Key::Up)],
+            Action::MoveDown => vec![K(Key::S), K(Key::Down)],
+            Action::RotateLeft => vec![K(Key::Z)],
+            Action::RotateRight => vec![K(Key::X)],
+            Action::ZoomIn => vec![ScrollUp, K(Key::Equal), K(Key::KpAdd)],
+            Action::ZoomOut => vec![ScrollDown, K(Key::Minus), K(Key::KpSubtract)],
+            Action::NextColor => vec![K(Key::C), Mouse(MouseButton::Button1)],
+            Action::PreviousColor => vec![Mouse(MouseButton::Button2)],
+            Action::ResetView => vec![K(Key::R), Mouse(MouseButton::Button3)],
+            Action::ToggleWireframe => vec![K(Key::L)],
+            Action::ToggleFullscreen => vec![K(Key::F), K(Key::F11)],
+            Action::ToggleVsync => vec![K(Key::V)],
+            Action::ToggleStats => vec![K(Key::F3)],
+            Action::Screenshot => vec![K(Key::F12), K(Key::PrintScreen)],
+            Action::ToggleRecording => vec![K(Key::F9)],
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// An input an action can be bound to. Written in config files as a key name
+// ("Q", "Escape", "F11", "KpAdd"), "mouse:left", "mouse:right",
+// "mouse:middle", "mouse:4" to "mouse:8", "scroll:up" or "scroll:down".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    ScrollUp,
+    ScrollDown,
+}
+
+impl Binding {
+    // Whether the event triggers this binding. Keys fire on press and repeat,
+    // mouse buttons on press. `InputMap::actions` ignores key repeats for
+    // actions that don't repeat.
+    pub fn matches(&self, event: &WindowEvent) -> bool {
+        match (*self, event) {
+            (Binding::Key(key), WindowEvent::Key(pressed, _, action, _)) => {
+                key == *pressed && *action != glfw::Action::Release
+            }
+            (Binding::Mouse(button), WindowEvent::MouseButton(pressed, action, _)) => {
+                button == *pressed && *action == glfw::Action::Press
+            }
+            (Binding::ScrollUp, WindowEvent::Scroll(_, y)) => *y > 0.0,
+            (Binding::ScrollDown, WindowEvent::Scroll(_, y)) => *y < 0.0,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(MouseButton::Button1) => write!(f, "mouse:left"),
+            Binding::Mouse(MouseButton::Button2) => write!(f, "mouse:right"),
+            Binding::Mouse(MouseButton::Button3) => write!(f, "mouse:middle"),
+            Binding::Mouse(button) => write!(f, "mouse:{}", *button as i32 + 1),
+            Binding::ScrollUp => write!(f, "scroll:up"),
+            Binding::ScrollDown => write!(f, "scroll:down"),
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Unknown input binding: {s}");
+        let name = s.trim().to_ascii_lowercase();
+
+        if let Some(button) = name.strip_prefix("mouse:") {
+            let number = match button {
+                "left" => 1,
+                "right" => 2,
+                "middle" => 3,
+                n => n.parse().map_err(|_| invalid())?,
+            };
+            return MouseButton::from_i32(number - 1)
+                .map(Binding::Mouse)
+                .ok_or_else(invalid);
+        }
+        match name.as_str() {
+            "scroll:up" => return Ok(Binding::ScrollUp),
+            "scroll:down" => return Ok(Binding::ScrollDown),
+            _ => {}
+        }
+
+        // Digits are spelled "Num0" by glfw
+        let name = match name.as_str() {
+            digit @ ("0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9") => {
+                format!("num{digit}")
+            }
+            "esc" => "escape".to_string(),
+            "return" => "enter".to_string(),
+            _ => name,
+        };
+        KEYS.iter()
+            .find(|key| format!("{key:?}").eq_ignore_ascii_case(&name))
+            .map(|&key| Binding::Key(key))
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+// Maps window events to actions. Starts from `Action::default_bindings`;
+// actions set in the config replace their defaults, an empty list unbinds
+// the action.
+#[derive(Debug, Clone)]
+pub struct InputMap {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl InputMap {
+    pub fn new(overrides: &HashMap<Action, Vec<Binding>>) -> Self {
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            let inputs = match overrides.get(&action) {
+                Some(inputs) => inputs.clone(),
+                None => action.default_bindings(),
+            };
+            bindings.extend(inputs.into_iter().map(|binding| (binding, action)));
+        }
+        Self { bindings }
+    }
+
+    pub fn bind(&mut self, binding: Binding, action: Action) {
+        if !self.bindings.contains(&(binding, action)) {
+            self.bindings.push((binding, action));
+        }
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.retain(|&(_, bound)| bound != action);
+    }
+
+    pub fn bindings(&self) -> &[(Binding, Action)] {
+        &self.bindings
+    }
+
+    pub fn bindings_for(&self, action: Action) -> impl Iterator<Item = Binding> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |&&(_, bound)| bound == action)
+            .map(|&(binding, _)| binding)
+    }
+
+    // Actions triggered by the event, in binding order without duplicates
+    pub fn actions(&self, event: &WindowEvent) -> Vec<Action> {
+        let repeat = matches!(event, WindowEvent::Key(_, _, glfw::Action::Repeat, _));
+        let mut actions = Vec::new();
+        for &(binding, action) in &self.bindings {
+            if binding.matches(event) && (action.repeats() || !repeat) && !actions.contains(&action)
+            {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::new(&HashMap::new())
+    }
+}
+
+// Keys that can be named in bindings, matched by their glfw variant name
+const KEYS: [Key; 120] = [
+    Key::Space,
+    Key::Apostrophe,
+    Key::Comma,
+    Key::Minus,
+    Key::Period,
+    Key::Slash,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::Semicolon,
+    Key::Equal,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::LeftBracket,
+    Key::Backslash,
+    Key::RightBracket,
+    Key::GraveAccent,
+    Key::World1,
+    Key::World2,
+    Key::Escape,
+    Key::Enter,
+    Key::Tab,
+    Key::Backspace,
+    Key::Insert,
+    Key::Delete,
+    Key::Right,
+    Key::Left,
+    Key::Down,
+    Key::Up,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Home,
+    Key::End,
+    Key::CapsLock,
+    Key::ScrollLock,
+    Key::NumLock,
+    Key::PrintScreen,
+    Key::Pause,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::F13,
+    Key::F14,
+    Key::F15,
+    Key::F16,
+    Key::F17,
+    Key::F18,
+    Key::F19,
+    Key::F20,
+    Key::F21,
+    Key::F22,
+    Key::F23,
+    Key::F24,
+    Key::F25,
+    Key::Kp0,
+    Key::Kp1,
+    Key::Kp2,
+    Key::Kp3,
+    Key::Kp4,
+    Key::Kp5,
+    Key::Kp6,
+    Key::Kp7,
+    Key::Kp8,
+    Key::Kp9,
+    Key::KpDecimal,
+    Key::KpDivide,
+    Key::KpMultiply,
+    Key::KpSubtract,
+    Key::KpAdd,
+    Key::KpEnter,
+    Key::KpEqual,
+    Key::LeftShift,
+    Key::LeftControl,
+    Key::LeftAlt,
+    Key::LeftSuper,
+    Key::RightShift,
+    Key::RightControl,
+    Key::RightAlt,
+    Key::RightSuper,
+    Key::Menu,
+];
+
+#[cfg(test)]
+mod tests {
+    use glfw::Modifiers;
+
+    use super::*;
+
+    fn binding(s: &str) -> Binding {
+        s.parse().unwrap_or_else(|e| panic!("{s}: {e}"))
+    }
+
+    fn key(key: Key, action: glfw::Action) -> WindowEvent {
+        WindowEvent::Key(key, 0, action, Modifiers::empty())
+    }
+
+    #[test]
+    fn key_names() {
+        assert_eq!(binding("Q"), Binding::Key(Key::Q));
+        assert_eq!(binding(" escape "), Binding::Key(Key::Escape));
+        assert_eq!(binding("Esc"), Binding::Key(Key::Escape));
+        assert_eq!(binding("return"), Binding::Key(Key::Enter));
+        assert_eq!(binding("F11"), Binding::Key(Key::F11));
+        assert_eq!(binding("kpadd"), Binding::Key(Key::KpAdd));
+    }
+
+    #[test]
+    fn digit_names() {
+        assert_eq!(binding("1"), Binding::Key(Key::Num1));
+        assert_eq!(binding("Num0"), Binding::Key(Key::Num0));
+        assert_eq!(binding("num9"), Binding::Key(Key::Num9));
+        assert_eq!(binding("Kp5"), Binding::Key(Key::Kp5));
+    }
+
+    #[test]
+    fn modifier_keys() {
+        // Modifiers bind on their own, combinations aren't supported
+        assert_eq!(binding("LeftShift"), Binding::Key(Key::LeftShift));
+        assert_eq!(binding("RightControl"), Binding::Key(Key::RightControl));
+        assert_eq!(binding("leftalt"), Binding::Key(Key::LeftAlt));
+        assert_eq!(binding("RightSuper"), Binding::Key(Key::RightSuper));
+        for combo in ["Ctrl+S", "LeftShift+A", "shift-f"] {
+            assert_eq!(
+                combo.parse::<Binding>(),
+                Err(format!("Unknown input binding: {combo}"))
+            );
+        }
+    }
+
+    #[test]
+    fn mouse_and_scroll() {
+        assert_eq!(binding("mouse:left"), Binding::Mouse(MouseButton::Button1));
+        assert_eq!(
+            binding("Mouse:Middle"),
+            Binding::Mouse(MouseButton::Button3)
+        );
+        assert_eq!(binding("mouse:8"), Binding::Mouse(MouseButton::Button8));
+        assert_eq!(binding("scroll:up"), Binding::ScrollUp);
+        assert_eq!(binding("SCROLL:DOWN"), Binding::ScrollDown);
+    }
+
+    #[test]
+    fn unknown_inputs() {
+        for name in [
+            "",
+            "Hyper",
+            "F26",
+            "10",
+            "mouse:0",
+            "mouse:9",
+            "mouse:side",
+            "scroll:left",
+        ] {
+            assert_eq!(
+                name.parse::<Binding>(),
+                Err(format!("Unknown input binding: {name}"))
+            );
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for action in Action::ALL {
+            for binding in action.default_bindings() {
+                assert_eq!(binding.to_string().parse(), Ok(binding));
+            }
+        }
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let overrides = HashMap::from([
+            (Action::Quit, vec![binding("F10")]),
+            (Action::ToggleWireframe, vec![]),
+        ]);
+        let map = InputMap::new(&overrides);
+        assert_eq!(
+            map.bindings_for(Action::Quit).collect::<Vec<_>>(),
+            [binding("F10")]
+        );
+        assert_eq!(map.bindings_for(Action::ToggleWireframe).count(), 0);
+        assert_eq!(
+            map.bindings_for(Action::ToggleVsync).collect::<Vec<_>>(),
+            Action::ToggleVsync.default_bindings()
+        );
+
+        let press = |k| map.actions(&key(k, glfw::Action::Press));
+        assert!(press(Key::Q).is_empty());
+        assert!(press(Key::L).is_empty());
+        assert_eq!(press(Key::F10), [Action::Quit]);
+    }
+
+    #[test]
+    fn bind_and_unbind() {
+        let mut map = InputMap::default();
+        map.bind(binding("C"), Action::ResetView);
+        map.bind(binding("C"), Action::ResetView);
+        assert_eq!(
+            map.actions(&key(Key::C, glfw::Action::Press)),
+            [Action::NextColor, Action::ResetView]
+        );
+        map.unbind(Action::NextColor);
+        assert_eq!(
+            map.actions(&key(Key::C, glfw::Action::Press)),
+            [Action::ResetView]
+        );
+    }
+
+    #[test]
+    fn repeats_only_continuous_actions() {
+        let map = InputMap::default();
+        let actions = |k, action| map.actions(&key(k, action));
+        assert_eq!(actions(Key::A, glfw::Action::Repeat), [Action::MoveLeft]);
+        assert_eq!(actions(Key::Equal, glfw::Action::Repeat), [Action::ZoomIn]);
+        assert_eq!(
+            actions(Key::F, glfw::Action::Press),
+            [Action::ToggleFullscreen]
+        );
+        for toggle in [Key::F, Key::L, Key::V, Key::F3, Key::F9, Key::F12, Key::Q] {
+            assert!(
+                actions(toggle, glfw::Action::Repeat).is_empty(),
+                "{toggle:?}"
+            );
+        }
+        assert!(actions(Key::A, glfw::Action::Release).is_empty());
+
+        let click = |action| {
+            map.actions(&WindowEvent::MouseButton(
+                MouseButton::Button1,
+                action,
+                Modifiers::empty(),
+            ))
+        };
+        assert_eq!(click(glfw::Action::Press), [Action::NextColor]);
+        assert!(click(glfw::Action::Release).is_empty());
+        assert_eq!(
+            map.actions(&WindowEvent::Scroll(0.0, -1.0)),
+            [Action::ZoomOut]
+        );
+        assert!(map.actions(&WindowEvent::Scroll(1.0, 0.0)).is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+pub mod app;
+pub mod backend;
+pub mod buffer;
+pub mod camera;
+pub mod capture;
+pub mod color;
+pub mod config;
+pub mod error;
+pub mod font;
+pub mod framebuffer;
+pub mod headless;
+pub mod input;
+pub mod math;
+pub mod mesh;
+pub mod postprocess;
+pub mod renderer;
+pub mod scene;
+pub mod shader;
+pub mod stats;
+pub mod text;
+pub mod texture;
+pub mod timing;
+pub mod watcher;
+
+pub use app::App;
+pub use buffer::{
+    Attribute, Buffer, BufferTarget, BufferUsage, ColorVertex, Index, PositionVertex, Vertex,
+    VertexArray, VertexLayout,
+};
+pub use camera::{Camera, Projection};
+pub use capture::FrameRecorder;
+pub use color::{Color, ParseColorError, gl_clear_color};
+pub use config::{Config, GlProfile, USAGE};
+pub use error::Error;
+pub use font::{Font, Glyph};
+pub use framebuffer::Framebuffer;
+pub use headless::{HeadlessApp, HeadlessContext};
+pub use input::{Action, Binding, InputMap};
+pub use math::{Mat4, Quat, Transform, Vec2, Vec3, Vec4};
+pub use mesh::{IndexBuffer, Mesh, MeshData, MeshVertex, Topology};
+pub use postprocess::{Effect, PostProcessor};
+pub use renderer::{
+    BACKGROUND_COLOR, CORNER_COLORS, Renderer, TRIANGLE_COLOR, TRIANGLE_VERTICES, Viewport,
+    render_software, triangle_vertices,
+};
+pub use scene::{Components, ObjectData, Scene, SceneData, ShaderKind, Shape};
+pub use shader::{
+    BLUR_FRAG_SHADER, DEFAULT_FRAG_SHADER, DEFAULT_VERT_SHADER, FXAA_FRAG_SHADER,
+    GRADIENT_FRAG_SHADER, GRADIENT_VERT_SHADER, GRAYSCALE_FRAG_SHADER, INVERT_FRAG_SHADER,
+    MESH_FRAG_SHADER, MESH_VERT_SHADER, Mat4Array, POST_FRAG_SHADER, POST_VERT_SHADER,
+    SHARPEN_FRAG_SHADER, Shader, ShaderError, ShaderLogHook, ShaderProgram, ShaderSources,
+    ShaderStage, TEXT_FRAG_SHADER, TEXT_VERT_SHADER, TEXTURE_FRAG_SHADER, TEXTURE_VERT_SHADER,
+    TONEMAP_FRAG_SHADER, Uniform, VIGNETTE_FRAG_SHADER, set_shader_log_hook,
+};
+pub use stats::{FrameSample, FrameStats, GpuTimer, Summary};
+pub use text::{TextRenderer, TextVertex};
+pub use texture::{Filter, Texture2D, TextureOptions, Wrap};
+pub use timing::{FixedTimestep, FrameLimiter};
+pub use watcher::FileWatcher;
+
+pub fn gl_get_string<'a>(name: gl::types::GLenum) -> &'a str {
+    let v = unsafe { gl::GetString(name) };
+    let v: &std::ffi::CStr = unsafe { std::ffi::CStr::from_ptr(v as *const i8) };
+    v.to_str().unwrap()
+}
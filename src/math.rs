@@ -0,0 +1,744 @@
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::shader::{Mat4Array, Uniform};
+
+// Component-wise operators shared by the vector types
+macro_rules! impl_vector {
+    ($name:ident, $n:literal, $($field:ident),+) => {
+        impl $name {
+            pub const ZERO: Self = Self { $($field: 0.0),+ };
+            pub const ONE: Self = Self { $($field: 1.0),+ };
+
+            pub const fn new($($field: f32),+) -> Self {
+                Self { $($field),+ }
+            }
+
+            pub const fn splat(v: f32) -> Self {
+                Self { $($field: v),+ }
+            }
+
+            pub fn dot(self, other: Self) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length(self) -> f32 {
+                self.dot(self).sqrt()
+            }
+
+            // Unit vector in the same direction, zero stays zero
+            pub fn normalize(self) -> Self {
+                let length = self.length();
+                if length == 0.0 { self } else { self / length }
+            }
+
+            pub fn lerp(self, other: Self, t: f32) -> Self {
+                self + (other - self) * t
+            }
+
+            // Component-wise
+            pub fn min(self, other: Self) -> Self {
+                Self { $($field: self.$field.min(other.$field)),+ }
+            }
+
+            pub fn max(self, other: Self) -> Self {
+                Self { $($field: self.$field.max(other.$field)),+ }
+            }
+
+            pub fn to_array(self) -> [f32; $n] {
+                [$(self.$field),+]
+            }
+        }
+
+        impl From<[f32; $n]> for $name {
+            fn from([$($field),+]: [f32; $n]) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl From<$name> for [f32; $n] {
+            fn from(v: $name) -> Self {
+                v.to_array()
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: f32) -> Self {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs;
+            }
+        }
+
+        // Component-wise product
+        impl Mul for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: Self) -> Self {
+                Self { $($field: self.$field * rhs.$field),+ }
+            }
+        }
+
+        impl Div<f32> for $name {
+            type Output = Self;
+
+            fn div(self, rhs: f32) -> Self {
+                Self { $($field: self.$field / rhs),+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl Uniform for $name {
+            fn set(&self, location: i32) {
+                self.to_array().set(location)
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl_vector!(Vec2, 2, x, y);
+impl_vector!(Vec3, 3, x, y, z);
+impl_vector!(Vec4, 4, x, y, z, w);
+
+impl Vec2 {
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl Vec3 {
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl Vec4 {
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+// Column-major 4x4 matrix, `cols[c][r]`. Transforms column vectors, so
+// `a * b` applies `b` first, same as in GLSL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub cols: Mat4Array,
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    pub const fn from_cols_array(cols: Mat4Array) -> Self {
+        Self { cols }
+    }
+
+    pub const fn to_cols_array(self) -> Mat4Array {
+        self.cols
+    }
+
+    pub fn col(&self, c: usize) -> Vec4 {
+        Vec4::from(self.cols[c])
+    }
+
+    pub fn row(&self, r: usize) -> Vec4 {
+        Vec4::new(
+            self.cols[0][r],
+            self.cols[1][r],
+            self.cols[2][r],
+            self.cols[3][r],
+        )
+    }
+
+    pub fn transpose(self) -> Self {
+        Self {
+            cols: std::array::from_fn(|c| self.row(c).to_array()),
+        }
+    }
+
+    pub fn translation(t: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.cols[3] = [t.x, t.y, t.z, 1.0];
+        m
+    }
+
+    pub fn scale(s: Vec3) -> Self {
+        let mut m = Self::IDENTITY;
+        m.cols[0][0] = s.x;
+        m.cols[1][1] = s.y;
+        m.cols[2][2] = s.z;
+        m
+    }
+
+    pub fn rotation(q: Quat) -> Self {
+        let Quat { x, y, z, w } = q.normalize();
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, yy, zz) = (x * x2, y * y2, z * z2);
+        let (xy, xz, yz) = (x * y2, x * z2, y * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        Self {
+            cols: [
+                [1.0 - (yy + zz), xy + wz, xz - wy, 0.0],
+                [xy - wz, 1.0 - (xx + zz), yz + wx, 0.0],
+                [xz + wy, yz - wx, 1.0 - (xx + yy), 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    // Maps the box to GL clip space, `near` and `far` are distances along -Z
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (w, h, d) = (right - left, top - bottom, far - near);
+        Self {
+            cols: [
+                [2.0 / w, 0.0, 0.0, 0.0],
+                [0.0, 2.0 / h, 0.0, 0.0],
+                [0.0, 0.0, -2.0 / d, 0.0],
+                [
+                    -(right + left) / w,
+                    -(top + bottom) / h,
+                    -(far + near) / d,
+                    1.0,
+                ],
+            ],
+        }
+    }
+
+    // Right-handed perspective with a vertical field of view in radians,
+    // same as `gluPerspective`
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).tan();
+        let d = near - far;
+        Self {
+            cols: [
+                [f / aspect_ratio, 0.0, 0.0, 0.0],
+                [0.0, f, 0.0, 0.0],
+                [0.0, 0.0, (far + near) / d, -1.0],
+                [0.0, 0.0, 2.0 * far * near / d, 0.0],
+            ],
+        }
+    }
+
+    // View matrix of an eye at `eye` looking at `target`, right-handed
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - eye).normalize();
+        let right = forward.cross(up).normalize();
+        let up = right.cross(forward);
+        Self {
+            cols: [
+                [right.x, up.x, -forward.x, 0.0],
+                [right.y, up.y, -forward.y, 0.0],
+                [right.z, up.z, -forward.z, 0.0],
+                [-right.dot(eye), -up.dot(eye), forward.dot(eye), 1.0],
+            ],
+        }
+    }
+
+    // Applies the matrix to a point, including the perspective divide
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        let v = *self * p.extend(1.0);
+        if v.w == 0.0 {
+            v.truncate()
+        } else {
+            v.truncate() / v.w
+        }
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        (*self * v.extend(0.0)).truncate()
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            cols: std::array::from_fn(|c| (self * rhs.col(c)).to_array()),
+        }
+    }
+}
+
+impl MulAssign for Mat4 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, v: Vec4) -> Vec4 {
+        self.col(0) * v.x + self.col(1) * v.y + self.col(2) * v.z + self.col(3) * v.w
+    }
+}
+
+impl From<Mat4Array> for Mat4 {
+    fn from(cols: Mat4Array) -> Self {
+        Self { cols }
+    }
+}
+
+impl Uniform for Mat4 {
+    fn set(&self, location: i32) {
+        self.cols.set(location)
+    }
+}
+
+// Rotation quaternion, `w` is the scalar part
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    // Counter-clockwise rotation by `angle` radians around `axis`
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Self::new(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    pub fn from_rotation_x(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::X, angle)
+    }
+
+    pub fn from_rotation_y(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Y, angle)
+    }
+
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Z, angle)
+    }
+
+    // Yaw around Y, then pitch around X, then roll around Z, in radians
+    pub fn from_euler(yaw: f32, pitch: f32, roll: f32) -> Self {
+        Self::from_rotation_y(yaw) * Self::from_rotation_x(pitch) * Self::from_rotation_z(roll)
+    }
+
+    pub fn length(self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            return Self::IDENTITY;
+        }
+        Self::new(
+            self.x / length,
+            self.y / length,
+            self.z / length,
+            self.w / length,
+        )
+    }
+
+    // Inverse rotation of a unit quaternion
+    pub fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    // Spherical interpolation along the shorter arc
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut dot = self.dot(other);
+        let other = if dot < 0.0 {
+            dot = -dot;
+            Self::new(-other.x, -other.y, -other.z, -other.w)
+        } else {
+            other
+        };
+
+        let (a, b) = if dot > 0.9995 {
+            // Nearly the same rotation, a linear blend is accurate enough
+            (1.0 - t, t)
+        } else {
+            let theta = dot.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+        Self::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        )
+        .normalize()
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        Mat4::rotation(self)
+    }
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+// Applies `rhs` first, then `self`
+impl Mul for Quat {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+// Rotates a vector
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(v) * 2.0;
+        v + t * self.w + q.cross(t)
+    }
+}
+
+// Translation, rotation and scale of an object in world space. The model
+// matrix scales first, then rotates, then translates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn with_translation(self, translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..self
+        }
+    }
+
+    pub fn with_rotation(self, rotation: Quat) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn with_scale(self, scale: Vec3) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        self.translation += offset;
+    }
+
+    // Adds a rotation on top of the current one
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::translation(self.translation)
+            * Mat4::rotation(self.rotation)
+            * Mat4::scale(self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_vec3(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < EPSILON, "{a:?} != {b:?}");
+    }
+
+    fn assert_mat4(a: Mat4, b: Mat4) {
+        let close = a
+            .cols
+            .iter()
+            .flatten()
+            .zip(b.cols.iter().flatten())
+            .all(|(x, y)| (x - y).abs() < EPSILON);
+        assert!(close, "{a:?} != {b:?}");
+    }
+
+    fn assert_quat(a: Quat, b: Quat) {
+        // q and -q are the same rotation
+        assert!((a.dot(b).abs() - 1.0).abs() < EPSILON, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn identity_and_multiply() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::scale(Vec3::splat(2.0));
+        assert_eq!(Mat4::IDENTITY * m, m);
+        assert_eq!(m * Mat4::IDENTITY, m);
+        // The right-hand side applies first
+        assert_vec3(m.transform_point(Vec3::ONE), Vec3::new(3.0, 4.0, 5.0));
+        assert_vec3(m.transform_vector(Vec3::ONE), Vec3::splat(2.0));
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.row(0), Vec4::new(2.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn rotations() {
+        let z = Quat::from_rotation_z(FRAC_PI_2);
+        assert_vec3(z * Vec3::X, Vec3::Y);
+        assert_vec3(z.to_mat4().transform_point(Vec3::X), Vec3::Y);
+        assert_vec3(Quat::from_rotation_x(FRAC_PI_2) * Vec3::Y, Vec3::Z);
+        assert_vec3(Quat::from_rotation_y(FRAC_PI_2) * Vec3::Z, Vec3::X);
+        // The axis doesn't have to be normalized
+        assert_quat(Quat::from_axis_angle(Vec3::Z * 5.0, FRAC_PI_2), z);
+        assert_quat(z * z.conjugate(), Quat::IDENTITY);
+        // Yaw is applied last
+        let euler = Quat::from_euler(FRAC_PI_2, FRAC_PI_2, 0.0);
+        assert_vec3(euler * Vec3::Y, Vec3::X);
+    }
+
+    #[test]
+    fn normalize() {
+        let q = Quat::new(0.0, 0.0, 3.0, 4.0).normalize();
+        assert!((q.length() - 1.0).abs() < EPSILON);
+        assert_eq!(q, Quat::new(0.0, 0.0, 0.6, 0.8));
+        assert_eq!(Quat::new(0.0, 0.0, 0.0, 0.0).normalize(), Quat::IDENTITY);
+        assert_eq!(Vec3::ZERO.normalize(), Vec3::ZERO);
+        assert_vec3(
+            Vec3::new(0.0, 3.0, 4.0).normalize(),
+            Vec3::new(0.0, 0.6, 0.8),
+        );
+    }
+
+    #[test]
+    fn slerp() {
+        let a = Quat::IDENTITY;
+        let b = Quat::from_rotation_z(FRAC_PI_2);
+        assert_quat(a.slerp(b, 0.0), a);
+        assert_quat(b.slerp(a, 0.0), b);
+        assert_quat(a.slerp(b, 1.0), b);
+        assert_quat(a.slerp(b, 0.5), Quat::from_rotation_z(FRAC_PI_4));
+        // Goes the short way around when the signs differ
+        let negated = Quat::new(-b.x, -b.y, -b.z, -b.w);
+        assert_quat(a.slerp(negated, 0.5), Quat::from_rotation_z(FRAC_PI_4));
+        // Nearly equal rotations fall back to a linear blend
+        let c = Quat::from_rotation_z(0.01);
+        assert_quat(a.slerp(c, 0.5), Quat::from_rotation_z(0.005));
+    }
+
+    #[test]
+    fn look_at_basis() {
+        let eye = Vec3::new(3.0, 2.0, 5.0);
+        let target = Vec3::new(1.0, 2.0, 1.0);
+        let view = Mat4::look_at(eye, target, Vec3::Y);
+        assert_vec3(view.transform_point(eye), Vec3::ZERO);
+        // The target ends up straight ahead, down -Z
+        let distance = (target - eye).length();
+        assert_vec3(view.transform_point(target), Vec3::new(0.0, 0.0, -distance));
+        assert_vec3(view.transform_vector(Vec3::Y), Vec3::Y);
+        // Rows are the orthonormal right / up / back basis
+        let right = view.row(0).truncate();
+        let up = view.row(1).truncate();
+        let back = view.row(2).truncate();
+        assert_vec3(right, (target - eye).cross(Vec3::Y).normalize());
+        assert_vec3(right.cross(up), back);
+        assert!(right.dot(up).abs() < EPSILON);
+
+        let default = Mat4::look_at(Vec3::Z, Vec3::ZERO, Vec3::Y);
+        assert_mat4(default, Mat4::translation(-Vec3::Z));
+    }
+
+    #[test]
+    fn perspective_depth() {
+        let (near, far) = (0.1, 100.0);
+        let projection = Mat4::perspective(FRAC_PI_2, 2.0, near, far);
+        // Near and far planes map to -1 and 1
+        assert!((projection.transform_point(Vec3::new(0.0, 0.0, -near)).z + 1.0).abs() < EPSILON);
+        assert!((projection.transform_point(Vec3::new(0.0, 0.0, -far)).z - 1.0).abs() < 1e-4);
+        // A 90 degree field of view reaches the top edge at y == depth, the
+        // aspect ratio squeezes x
+        let edge = projection.transform_point(Vec3::new(2.0, 1.0, -1.0));
+        assert!((edge.x - 1.0).abs() < EPSILON && (edge.y - 1.0).abs() < EPSILON);
+        // Depth increases with distance
+        let mid = projection.transform_point(Vec3::new(0.0, 0.0, -10.0)).z;
+        assert!(-1.0 < mid && mid < 1.0);
+        assert!(projection.transform_point(Vec3::new(0.0, 0.0, -20.0)).z > mid);
+    }
+
+    #[test]
+    fn orthographic() {
+        let projection = Mat4::orthographic(-2.0, 2.0, -1.0, 1.0, 0.5, 10.0);
+        assert_vec3(
+            projection.transform_point(Vec3::new(-2.0, -1.0, -0.5)),
+            Vec3::new(-1.0, -1.0, -1.0),
+        );
+        assert_vec3(
+            projection.transform_point(Vec3::new(2.0, 1.0, -10.0)),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
+        assert_vec3(
+            projection.transform_point(Vec3::new(1.0, 0.5, -5.25)),
+            Vec3::new(0.5, 0.5, 0.0),
+        );
+    }
+
+    #[test]
+    fn transform_matrix() {
+        let transform = Transform::from_translation(Vec3::new(1.0, 0.0, 0.0))
+            .with_rotation(Quat::from_rotation_z(FRAC_PI_2))
+            .with_scale(Vec3::new(2.0, 1.0, 1.0));
+        // Scaled, then rotated, then moved
+        assert_vec3(
+            transform.matrix().transform_point(Vec3::X),
+            Vec3::new(1.0, 2.0, 0.0),
+        );
+        assert_eq!(Transform::IDENTITY.matrix(), Mat4::IDENTITY);
+
+        let halfway = Transform::IDENTITY.lerp(&transform, 0.5);
+        assert_vec3(halfway.translation, Vec3::new(0.5, 0.0, 0.0));
+        assert_vec3(halfway.scale, Vec3::new(1.5, 1.0, 1.0));
+        assert_quat(halfway.rotation, Quat::from_rotation_z(FRAC_PI_4));
+    }
+}
@@ -0,0 +1,527 @@
+use std::path::Path;
+
+use crate::{
+    Attribute, Buffer, BufferTarget, BufferUsage, Error, Index, Mat4, Vec3, Vertex, VertexArray,
+    VertexLayout,
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    // Only used by the gradient shaders, white unless set
+    pub color: [f32; 4],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+impl Default for MeshVertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            color: [1.0; 4],
+            normal: [0.0; 3],
+            uv: [0.0; 2],
+        }
+    }
+}
+
+impl Vertex for MeshVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .with(Attribute::Position)
+            .with(Attribute::Color)
+            .with(Attribute::Normal)
+            .with(Attribute::Uv)
+    }
+}
+
+// How the vertices (or the indices, when there are any) are put together
+// into primitives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    Points,
+    // Every two vertices
+    Lines,
+    LineStrip,
+    // Every three vertices
+    #[default]
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl Topology {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            Topology::Points => gl::POINTS,
+            Topology::Lines => gl::LINES,
+            Topology::LineStrip => gl::LINE_STRIP,
+            Topology::Triangles => gl::TRIANGLES,
+            Topology::TriangleStrip => gl::TRIANGLE_STRIP,
+            Topology::TriangleFan => gl::TRIANGLE_FAN,
+        }
+    }
+}
+
+// Vertices and the indices that build primitives out of them, on the CPU
+// side. Without indices the vertices are used in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    pub topology: Topology,
+}
+
+impl MeshData {
+    // Square of side 1 in the XY plane facing +Z, two triangles sharing a
+    // diagonal
+    pub fn quad() -> Self {
+        let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        Self {
+            vertices: corners
+                .iter()
+                .map(|&[u, v]| MeshVertex {
+                    position: [u - 0.5, v - 0.5, 0.0],
+                    normal: [0.0, 0.0, 1.0],
+                    uv: [u, v],
+                    ..MeshVertex::default()
+                })
+                .collect(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+            topology: Topology::Triangles,
+        }
+    }
+
+    // Lines of a `columns` x `rows` grid of side 1 in the XY plane. Every
+    // crossing is one vertex shared by the lines through it.
+    pub fn grid(columns: u32, rows: u32) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let mut vertices = Vec::new();
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let (u, v) = (column as f32 / columns as f32, row as f32 / rows as f32);
+                vertices.push(MeshVertex {
+                    position: [u - 0.5, v - 0.5, 0.0],
+                    normal: [0.0, 0.0, 1.0],
+                    uv: [u, v],
+                    ..MeshVertex::default()
+                });
+            }
+        }
+
+        let index = |column: u32, row: u32| row * (columns + 1) + column;
+        let mut indices = Vec::new();
+        for row in 0..=rows {
+            for column in 0..=columns {
+                if column < columns {
+                    indices.extend([index(column, row), index(column + 1, row)]);
+                }
+                if row < rows {
+                    indices.extend([index(column, row), index(column, row + 1)]);
+                }
+            }
+        }
+        Self {
+            vertices,
+            indices,
+            topology: Topology::Lines,
+        }
+    }
+
+    // Picks the loader from the extension: `.obj`, `.gltf` or `.glb`
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => Self::from_obj(path),
+            Some("gltf" | "glb") => Self::from_gltf(path),
+            _ => Err(mesh_error(
+                path,
+                "unknown model format, expected .obj, .gltf or .glb",
+            )),
+        }
+    }
+
+    // All objects of a Wavefront OBJ file, faces triangulated. Materials are
+    // ignored.
+    pub fn from_obj(path: &Path) -> Result<Self, Error> {
+        let options = tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ..tobj::LoadOptions::default()
+        };
+        let (models, _materials) =
+            tobj::load_obj(path, &options).map_err(|e| mesh_error(path, e))?;
+
+        let mut data = Self::default();
+        for model in models {
+            let mesh = model.mesh;
+            let mut part = Self {
+                vertices: (0..mesh.positions.len() / 3)
+                    .map(|i| MeshVertex {
+                        position: [
+                            mesh.positions[i * 3],
+                            mesh.positions[i * 3 + 1],
+                            mesh.positions[i * 3 + 2],
+                        ],
+                        normal: mesh
+                            .normals
+                            .get(i * 3..i * 3 + 3)
+                            .map_or([0.0; 3], |n| [n[0], n[1], n[2]]),
+                        uv: mesh
+                            .texcoords
+                            .get(i * 2..i * 2 + 2)
+                            .map_or([0.0; 2], |uv| [uv[0], uv[1]]),
+                        ..MeshVertex::default()
+                    })
+                    .collect(),
+                indices: mesh.indices,
+                topology: Topology::Triangles,
+            };
+            part.check_indices()
+                .map_err(|e| mesh_error(path, format!("{}: {e}", model.name)))?;
+            if mesh.normals.is_empty() {
+                part.compute_normals();
+            }
+            data.append(part);
+        }
+        Ok(data)
+    }
+
+    // The triangle primitives of the default scene of a glTF 2.0 file, with
+    // the node transforms applied. Other primitive modes are skipped.
+    pub fn from_gltf(path: &Path) -> Result<Self, Error> {
+        let gltf::Gltf { document, blob } =
+            gltf::Gltf::open(path).map_err(|e| mesh_error(path, e))?;
+        let buffers = gltf::import_buffers(&document, path.parent(), blob)
+            .map_err(|e| mesh_error(path, e))?;
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| mesh_error(path, "no scene"))?;
+
+        let mut data = Self::default();
+        for node in scene.nodes() {
+            data.append_gltf_node(&node, Mat4::IDENTITY, &buffers)
+                .map_err(|e| mesh_error(path, e))?;
+        }
+        Ok(data)
+    }
+
+    fn append_gltf_node(
+        &mut self,
+        node: &gltf::Node,
+        parent: Mat4,
+        buffers: &[gltf::buffer::Data],
+    ) -> Result<(), String> {
+        let transform = parent * Mat4::from(node.transform().matrix());
+        for primitive in node.mesh().iter().flat_map(|mesh| mesh.primitives()) {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|d| &d[..]));
+            let Some(positions) = reader.read_positions() else {
+                continue;
+            };
+
+            let mut part = Self {
+                vertices: positions
+                    .map(|position| MeshVertex {
+                        position: transform.transform_point(position.into()).to_array(),
+                        ..MeshVertex::default()
+                    })
+                    .collect(),
+                indices: Vec::new(),
+                topology: Topology::Triangles,
+            };
+            part.indices = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..part.vertices.len() as u32).collect(),
+            };
+            part.check_indices()
+                .map_err(|e| format!("node {}: {e}", node.index()))?;
+            if let Some(uvs) = reader.read_tex_coords(0) {
+                for (vertex, uv) in part.vertices.iter_mut().zip(uvs.into_f32()) {
+                    vertex.uv = uv;
+                }
+            }
+            match reader.read_normals() {
+                Some(normals) => {
+                    for (vertex, normal) in part.vertices.iter_mut().zip(normals) {
+                        vertex.normal = transform
+                            .transform_vector(normal.into())
+                            .normalize()
+                            .to_array();
+                    }
+                }
+                None => part.compute_normals(),
+            }
+            self.append(part);
+        }
+
+        for child in node.children() {
+            self.append_gltf_node(&child, transform, buffers)?;
+        }
+        Ok(())
+    }
+
+    // Whether every index refers to one of the vertices
+    pub fn check_indices(&self) -> Result<(), String> {
+        match self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= self.vertices.len())
+        {
+            Some(index) => Err(format!(
+                "index {index} out of range for {} vertices",
+                self.vertices.len()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Adds the primitives of `other` after the ones already here. Both have
+    // to be indexed lists of the same topology, strips and fans can't be
+    // joined.
+    pub fn append(&mut self, other: MeshData) {
+        debug_assert_eq!(self.topology, other.topology);
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|index| index + offset));
+    }
+
+    // Smooth normals of an indexed triangle list: each vertex gets the
+    // average of the faces around it, weighted by their area
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| Vec3::from(self.vertices[triangle[i] as usize].position));
+            let normal = (b - a).cross(c - a);
+            for &index in triangle {
+                normals[index as usize] += normal;
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.normalize().to_array();
+        }
+    }
+
+    // Smallest and largest corner of the axis-aligned bounding box, None
+    // without vertices
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut positions = self.vertices.iter().map(|v| Vec3::from(v.position));
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(min, max), p| (min.min(p), max.max(p))))
+    }
+}
+
+// Index buffer of a `Mesh`. 16-bit indices are used whenever every index
+// fits, which halves the buffer size.
+pub enum IndexBuffer {
+    U16(Buffer<u16>),
+    U32(Buffer<u32>),
+}
+
+impl IndexBuffer {
+    pub fn new(indices: &[u32]) -> Self {
+        let short: Option<Vec<u16>> = indices
+            .iter()
+            .map(|&index| u16::try_from(index).ok())
+            .collect();
+        if let Some(indices) = short {
+            IndexBuffer::U16(Buffer::new(
+                BufferTarget::ElementArray,
+                BufferUsage::Static,
+                &indices,
+            ))
+        } else {
+            IndexBuffer::U32(Buffer::new(
+                BufferTarget::ElementArray,
+                BufferUsage::Static,
+                indices,
+            ))
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IndexBuffer::U16(buffer) => buffer.len(),
+            IndexBuffer::U32(buffer) => buffer.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn gl_type(&self) -> gl::types::GLenum {
+        match self {
+            IndexBuffer::U16(_) => u16::GL_TYPE,
+            IndexBuffer::U32(_) => u32::GL_TYPE,
+        }
+    }
+
+    fn attach(&self, vertex_array: &VertexArray) {
+        match self {
+            IndexBuffer::U16(buffer) => vertex_array.set_index_buffer(buffer),
+            IndexBuffer::U32(buffer) => vertex_array.set_index_buffer(buffer),
+        }
+    }
+}
+
+// GPU copy of a `MeshData`: vertex and index buffers tied together by a VAO.
+// Needs a current GL context, deleted on Drop.
+pub struct Mesh {
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer<MeshVertex>,
+    // None draws the vertices in order
+    index_buffer: Option<IndexBuffer>,
+    topology: Topology,
+}
+
+impl Mesh {
+    // Fails when an index is out of range. The error has no path, data
+    // from files is checked by its loader already.
+    pub fn new(data: &MeshData) -> Result<Self, Error> {
+        data.check_indices()
+            .map_err(|e| mesh_error(Path::new(""), e))?;
+        let vertex_buffer = Buffer::new(BufferTarget::Array, BufferUsage::Static, &data.vertices);
+        vertex_buffer.unbind();
+        let vertex_array = VertexArray::new();
+        vertex_array.set_vertex_buffer(&vertex_buffer);
+
+        let index_buffer = (!data.indices.is_empty()).then(|| {
+            let index_buffer = IndexBuffer::new(&data.indices);
+            index_buffer.attach(&vertex_array);
+            index_buffer
+        });
+        Ok(Self {
+            vertex_array,
+            vertex_buffer,
+            index_buffer,
+            topology: data.topology,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::new(&MeshData::load(path)?)
+    }
+
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vertex_array
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<MeshVertex> {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_ref()
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    // Draws the same buffers as another kind of primitive, e.g. `Points` to
+    // see the vertices of a triangle mesh
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
+    // Draws the primitives with the bound program
+    pub fn draw(&self) {
+        let mode = self.topology.gl_enum();
+        self.vertex_array.bind();
+        unsafe {
+            match &self.index_buffer {
+                Some(indices) => gl::DrawElements(
+                    mode,
+                    indices.len() as i32,
+                    indices.gl_type(),
+                    std::ptr::null(),
+                ),
+                None => gl::DrawArrays(mode, 0, self.vertex_buffer.len() as i32),
+            }
+        }
+        self.vertex_array.unbind();
+    }
+}
+
+fn mesh_error(path: &Path, message: impl ToString) -> Error {
+    Error::Mesh {
+        path: path.to_path_buf(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // A triangle whose third index points past its three vertices
+    const BAD_GLTF: &str = r#"{"asset":{"version":"2.0"},"scene":0,"scenes":[{"nodes":[0]}],"nodes":[{"mesh":0}],"meshes":[{"primitives":[{"attributes":{"POSITION":0},"indices":1}]}],"buffers":[{"byteLength":44,"uri":"data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAcAAAA="}],"bufferViews":[{"buffer":0,"byteOffset":0,"byteLength":36},{"buffer":0,"byteOffset":36,"byteLength":6}],"accessors":[{"bufferView":0,"componentType":5126,"count":3,"type":"VEC3","min":[0,0,0],"max":[1,1,0]},{"bufferView":1,"componentType":5123,"count":3,"type":"SCALAR"}]}"#;
+
+    // Empty directory under the system temp directory, unique per test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rust-triangle-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn grid_shares_vertices() {
+        let grid = MeshData::grid(8, 4);
+        assert_eq!(grid.vertices.len(), 9 * 5);
+        // One line per edge between neighbouring crossings
+        assert_eq!(grid.indices.len(), 2 * (8 * 5 + 9 * 4));
+        assert!(grid.check_indices().is_ok());
+    }
+
+    #[test]
+    fn out_of_range_indices() {
+        let mut quad = MeshData::quad();
+        assert!(quad.check_indices().is_ok());
+        quad.vertices.truncate(3);
+        assert_eq!(
+            quad.check_indices(),
+            Err("index 3 out of range for 3 vertices".to_string())
+        );
+        // Checked before anything is uploaded, no GL context needed
+        let error = Mesh::new(&quad).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Invalid mesh: index 3 out of range for 3 vertices"
+        );
+    }
+
+    #[test]
+    fn out_of_range_obj_indices() {
+        let dir = temp_dir("bad-obj");
+        let path = dir.join("bad.obj");
+        std::fs::write(&path, "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 9\n").unwrap();
+        let result = MeshData::load(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(Error::Mesh { path: p, .. }) if p == path));
+    }
+
+    #[test]
+    fn out_of_range_gltf_indices() {
+        let dir = temp_dir("bad-gltf");
+        let path = dir.join("bad.gltf");
+        std::fs::write(&path, BAD_GLTF).unwrap();
+        let error = MeshData::load(&path).err().unwrap().to_string();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(
+            error.ends_with("bad.gltf: node 0: index 7 out of range for 3 vertices"),
+            "{error}"
+        );
+    }
+}
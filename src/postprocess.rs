@@ -0,0 +1,253 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::shader::{
+    BLUR_FRAG_SHADER, FXAA_FRAG_SHADER, GRAYSCALE_FRAG_SHADER, INVERT_FRAG_SHADER,
+    POST_FRAG_SHADER, POST_VERT_SHADER, SHARPEN_FRAG_SHADER, TONEMAP_FRAG_SHADER, U_SCENE,
+    U_TEXEL_SIZE, U_TIME, VIGNETTE_FRAG_SHADER,
+};
+use crate::{
+    Error, FileWatcher, Framebuffer, ShaderProgram, ShaderSources, Texture2D, VertexArray,
+};
+
+// Texture unit the input of a pass is bound to
+const SCENE_UNIT: u32 = 0;
+
+// A full-screen pass over the frame. `Shader` passes are fragment shader
+// files using the same inputs as the built-in ones: `v_uv`, `u_scene`,
+// `u_texel_size` and `u_time`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Effect {
+    Grayscale,
+    Invert,
+    Blur,
+    Sharpen,
+    Vignette,
+    Fxaa,
+    ToneMap,
+    Shader(PathBuf),
+}
+
+impl Effect {
+    fn sources(&self) -> ShaderSources {
+        let (file, frag) = match self {
+            Effect::Grayscale => (None, GRAYSCALE_FRAG_SHADER),
+            Effect::Invert => (None, INVERT_FRAG_SHADER),
+            Effect::Blur => (None, BLUR_FRAG_SHADER),
+            Effect::Sharpen => (None, SHARPEN_FRAG_SHADER),
+            Effect::Vignette => (None, VIGNETTE_FRAG_SHADER),
+            Effect::Fxaa => (None, FXAA_FRAG_SHADER),
+            Effect::ToneMap => (None, TONEMAP_FRAG_SHADER),
+            Effect::Shader(path) => (Some(path.clone()), POST_FRAG_SHADER),
+        };
+        ShaderSources::new(None, file).with_defaults(POST_VERT_SHADER, frag)
+    }
+}
+
+// A built-in effect name, or the path of a shader file. Other names are
+// rejected so a typo isn't taken for a missing file.
+impl FromStr for Effect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grayscale" => Ok(Effect::Grayscale),
+            "invert" => Ok(Effect::Invert),
+            "blur" => Ok(Effect::Blur),
+            "sharpen" => Ok(Effect::Sharpen),
+            "vignette" => Ok(Effect::Vignette),
+            "fxaa" => Ok(Effect::Fxaa),
+            "tonemap" => Ok(Effect::ToneMap),
+            _ if Path::new(s).extension().is_some() => Ok(Effect::Shader(s.into())),
+            _ => Err(format!("Unknown effect: {s}")),
+        }
+    }
+}
+
+impl TryFrom<String> for Effect {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+// An effect and its compiled program
+struct Pass {
+    effect: Effect,
+    sources: ShaderSources,
+    program: ShaderProgram,
+}
+
+// Chain of effects applied to a rendered frame, each pass reading the result
+// of the one before. Shader file passes are watched and reloaded like the
+// scene shaders. Needs a current GL context.
+pub struct PostProcessor {
+    passes: Vec<Pass>,
+    // Passes draw into these in turn, at the size of the frame
+    targets: Vec<Framebuffer>,
+    // Core profiles can't draw without a vertex array, even an empty one
+    vertex_array: VertexArray,
+    watcher: Option<FileWatcher>,
+}
+
+impl PostProcessor {
+    // `width` x `height` is the size of the frames `apply` gets
+    pub fn new(effects: &[Effect], width: u32, height: u32) -> Result<Self, Error> {
+        let passes = effects
+            .iter()
+            .map(|effect| {
+                let sources = effect.sources();
+                let program = sources.build()?;
+                Ok(Pass {
+                    effect: effect.clone(),
+                    sources,
+                    program,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        // Two are enough to ping-pong between
+        let targets = (0..passes.len().min(2))
+            .map(|_| Framebuffer::new(width, height, 0))
+            .collect::<Result<_, _>>()?;
+
+        let files: Vec<&Path> = passes
+            .iter()
+            .flat_map(|pass| pass.sources.files())
+            .collect();
+        let watcher = (!files.is_empty()).then(|| FileWatcher::new(&files));
+
+        Ok(Self {
+            passes,
+            targets,
+            vertex_array: VertexArray::new(),
+            watcher,
+        })
+    }
+
+    pub fn effects(&self) -> impl Iterator<Item = &Effect> {
+        self.passes.iter().map(|pass| &pass.effect)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    // Follows a change of the frame size
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        for target in &mut self.targets {
+            target.resize(width, height)?;
+        }
+        Ok(())
+    }
+
+    // Rebuilds every pass. If one fails to compile, all programs stay as
+    // they are.
+    pub fn reload_shaders(&mut self) -> Result<(), Error> {
+        let programs = self
+            .passes
+            .iter()
+            .map(|pass| pass.sources.build())
+            .collect::<Result<Vec<_>, _>>()?;
+        for (pass, program) in self.passes.iter_mut().zip(programs) {
+            pass.program = program;
+        }
+        Ok(())
+    }
+
+    // Reloads the passes if any of their shader files changed
+    pub fn hot_reload(&mut self) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        if !watcher.poll() {
+            return;
+        }
+
+        match self.reload_shaders() {
+            Ok(()) => println!("Effects reloaded"),
+            Err(e) => eprintln!("Effect reload failed, keeping the last good programs: {e}"),
+        }
+    }
+
+    // Runs the passes over `scene` and returns the framebuffer holding the
+    // result, bound. None without passes. The GL viewport is restored
+    // afterwards.
+    pub fn apply(&self, scene: &Texture2D, time: f32) -> Option<&Framebuffer> {
+        let (width, height) = scene.size();
+        let texel_size = [1.0 / width as f32, 1.0 / height as f32];
+
+        let mut viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Viewport(0, 0, width as i32, height as i32);
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            gl::Disable(gl::BLEND);
+        }
+        self.vertex_array.bind();
+
+        let mut input = scene;
+        let mut output = None;
+        for (pass, target) in self.passes.iter().zip(self.targets.iter().cycle()) {
+            debug_assert_eq!(target.size(), (width, height), "effect target size differs");
+            let program = &pass.program;
+            program.set_uniform(U_SCENE, &(SCENE_UNIT as i32));
+            program.set_uniform(U_TEXEL_SIZE, &texel_size);
+            program.set_uniform(U_TIME, &time);
+            program.bind();
+            target.bind();
+            input.bind(SCENE_UNIT);
+            unsafe { gl::DrawArrays(gl::TRIANGLES, 0, 3) };
+
+            input = target
+                .color_texture()
+                .expect("effect targets are single-sampled");
+            output = Some(target);
+        }
+
+        input.unbind(SCENE_UNIT);
+        self.vertex_array.unbind();
+        unsafe { gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]) };
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn effect_names() {
+        assert_eq!("tonemap".parse(), Ok(Effect::ToneMap));
+        assert_eq!(
+            "shaders/crt.frag".parse(),
+            Ok(Effect::Shader("shaders/crt.frag".into()))
+        );
+        assert_eq!(
+            "sparkle".parse::<Effect>(),
+            Err("Unknown effect: sparkle".to_string())
+        );
+    }
+
+    #[test]
+    fn effects_argument() {
+        let mut config = Config::default();
+        let args = ["--effects", "fxaa, vignette,shaders/crt.frag"].map(String::from);
+        config.apply_args(args).unwrap();
+        assert_eq!(
+            config.effects,
+            [
+                Effect::Fxaa,
+                Effect::Vignette,
+                Effect::Shader("shaders/crt.frag".into())
+            ]
+        );
+
+        let args = ["--effects", "sparkle"].map(String::from);
+        assert!(config.apply_args(args).is_err());
+    }
+}
@@ -0,0 +1,497 @@
+use crate::backend::{GlBackend, GlMesh, RenderBackend, SoftwareBackend, render_frame};
+use crate::shader::{
+    GRADIENT_FRAG_SHADER, GRADIENT_VERT_SHADER, MESH_FRAG_SHADER, MESH_VERT_SHADER,
+    TEXTURE_FRAG_SHADER, TEXTURE_VERT_SHADER, U_COLOR, U_MODEL, U_MVP, U_TEXTURE, U_TIME,
+};
+use crate::{
+    Camera, Color, ColorVertex, Config, Error, FileWatcher, Mat4, Mesh, MeshData, PositionVertex,
+    Quat, Scene, ShaderProgram, ShaderSources, Texture2D, TextureOptions, Transform,
+    gl_clear_color,
+};
+
+pub const BACKGROUND_COLOR: Color = Color::new(0.12, 0.12, 0.12, 1.0);
+pub const TRIANGLE_COLOR: Color = Color::new(1.0, 0.7, 0.2, 1.0);
+
+// Triangle Coords (X, Y, Z)
+pub const TRIANGLE_VERTICES: [PositionVertex; 3] = [
+    PositionVertex::new(0.5, 0.5, 0.0),
+    PositionVertex::new(-0.5, 0.5, 0.0),
+    PositionVertex::new(0.0, -0.5, 0.0),
+];
+
+// Classic RGB gradient, in the order of `TRIANGLE_VERTICES`
+pub const CORNER_COLORS: [Color; 3] = [Color::RED, Color::LIME, Color::BLUE];
+
+// Vertical field of view of the camera framing a model, in degrees
+const MODEL_FOV_Y: f32 = 45.0;
+// Texture unit of the model texture
+const TEXTURE_UNIT: u32 = 0;
+
+// `MeshData::quad` stretched to the aspect ratio of a `width` x `height` image
+fn image_quad((width, height): (u32, u32)) -> MeshData {
+    let mut quad = MeshData::quad();
+    let aspect_ratio = width as f32 / height.max(1) as f32;
+    for vertex in &mut quad.vertices {
+        vertex.position[0] *= aspect_ratio;
+    }
+    quad
+}
+
+// `TRIANGLE_VERTICES` with a color per corner
+pub fn triangle_vertices(corner_colors: [Color; 3]) -> [ColorVertex; 3] {
+    std::array::from_fn(|i| ColorVertex::new(TRIANGLE_VERTICES[i].position, corner_colors[i]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Viewport {
+    // Largest centered area of the framebuffer with the given aspect ratio,
+    // or the whole framebuffer
+    pub fn fit(width: i32, height: i32, aspect_ratio: Option<f32>) -> Self {
+        let full = Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let Some(aspect_ratio) = aspect_ratio else {
+            return full;
+        };
+        if width <= 0 || height <= 0 {
+            return full;
+        }
+
+        if width as f32 / height as f32 > aspect_ratio {
+            let fitted = (height as f32 * aspect_ratio).round() as i32;
+            Self {
+                x: (width - fitted) / 2,
+                width: fitted,
+                ..full
+            }
+        } else {
+            let fitted = (width as f32 / aspect_ratio).round() as i32;
+            Self {
+                y: (height - fitted) / 2,
+                height: fitted,
+                ..full
+            }
+        }
+    }
+}
+
+// Owns the shader program and buffers of the triangle pipeline.
+// Needs a current GL context with the `gl` functions already loaded.
+pub struct Renderer {
+    shader_program: ShaderProgram,
+    shader_sources: ShaderSources,
+    shader_watcher: Option<FileWatcher>,
+    clear_color: Color,
+    // Uploaded as `u_color`, `u_mvp` and `u_time` before every draw
+    color: Color,
+    transform: Transform,
+    // State before the last `update`, blended with the current one by `render`
+    previous_transform: Transform,
+    // Seconds of simulated time
+    time: f32,
+    previous_time: f32,
+    // Radians per second around Z
+    spin: f32,
+    // Without a camera the transformed vertices are used as clip space
+    // coordinates directly
+    camera: Option<Camera>,
+    wireframe: bool,
+    // Width / height the scene is laid out for when the aspect ratio is kept
+    aspect_ratio: Option<f32>,
+    viewport: Viewport,
+    mesh: GlMesh,
+    // Drawn instead of the triangle when set
+    model: Option<Mesh>,
+    // Bound to `TEXTURE_UNIT` while the model is drawn
+    texture: Option<Texture2D>,
+    // Drawn instead of the triangle and the model when set
+    scene: Option<Scene>,
+    backend: GlBackend,
+}
+
+impl Renderer {
+    pub fn new(config: &Config) -> Result<Self, Error> {
+        let mut shader_sources =
+            ShaderSources::new(config.vert_shader.clone(), config.frag_shader.clone());
+        let texture_options = TextureOptions {
+            filter: config.texture_filter,
+            wrap: config.texture_wrap,
+            mipmaps: true,
+        };
+        let texture = config
+            .texture
+            .as_deref()
+            .map(|path| Texture2D::load(path, &texture_options))
+            .transpose()?;
+        let mut model = config.model.as_deref().map(MeshData::load).transpose()?;
+        if model.is_none() {
+            model = texture.as_ref().map(|texture| image_quad(texture.size()));
+        }
+        let scene = config.scene.as_deref().map(Scene::load).transpose()?;
+
+        if texture.is_some() {
+            shader_sources = shader_sources.with_defaults(TEXTURE_VERT_SHADER, TEXTURE_FRAG_SHADER);
+        } else if model.is_some() {
+            shader_sources = shader_sources.with_defaults(MESH_VERT_SHADER, MESH_FRAG_SHADER);
+        } else if config.gradient {
+            shader_sources =
+                shader_sources.with_defaults(GRADIENT_VERT_SHADER, GRADIENT_FRAG_SHADER);
+        }
+        let shader_program = shader_sources.build()?;
+
+        let shader_files = shader_sources.files();
+        let shader_watcher = (!shader_files.is_empty()).then(|| FileWatcher::new(&shader_files));
+
+        let mut backend = GlBackend::new();
+        let mesh = backend.upload_mesh(&triangle_vertices(config.corner_colors));
+        // A lone image is shown flat, models in perspective
+        let model_camera = if config.model.is_some() {
+            Camera::perspective(MODEL_FOV_Y.to_radians())
+        } else {
+            Camera::orthographic(2.0)
+        };
+        let camera = match &scene {
+            Some(scene) => Some(scene.camera()),
+            None => model
+                .as_ref()
+                .and_then(MeshData::bounds)
+                .map(|(min, max)| model_camera.framing(min, max)),
+        }
+        .map(|mut camera| {
+            camera.set_aspect_ratio(config.width as i32, config.height as i32);
+            camera
+        });
+
+        Ok(Self {
+            shader_program,
+            shader_sources,
+            shader_watcher,
+            clear_color: config.clear_color,
+            color: TRIANGLE_COLOR,
+            transform: Transform::IDENTITY,
+            previous_transform: Transform::IDENTITY,
+            time: 0.0,
+            previous_time: 0.0,
+            spin: config.spin.to_radians(),
+            camera,
+            wireframe: false,
+            aspect_ratio: config
+                .keep_aspect
+                .then(|| config.width as f32 / config.height.max(1) as f32),
+            viewport: Viewport {
+                x: 0,
+                y: 0,
+                width: config.width as i32,
+                height: config.height as i32,
+            },
+            mesh,
+            model: model.as_ref().map(Mesh::new).transpose()?,
+            texture,
+            scene,
+            backend,
+        })
+    }
+
+    pub fn shader_program(&self) -> &ShaderProgram {
+        &self.shader_program
+    }
+
+    pub fn mesh(&self) -> &GlMesh {
+        &self.mesh
+    }
+
+    pub fn model(&self) -> Option<&Mesh> {
+        self.model.as_ref()
+    }
+
+    // Replaces the model, None goes back to the triangle. The shaders and
+    // camera are left as they are.
+    pub fn set_model(&mut self, model: Option<Mesh>) {
+        self.model = model;
+    }
+
+    pub fn scene(&self) -> Option<&Scene> {
+        self.scene.as_ref()
+    }
+
+    // Replaces the scene and looks at it with its camera. None goes back to
+    // the model or the triangle, keeping the camera.
+    pub fn set_scene(&mut self, scene: Option<Scene>) {
+        if let Some(scene) = &scene {
+            self.set_camera(Some(scene.camera()));
+        }
+        self.scene = scene;
+    }
+
+    pub fn texture(&self) -> Option<&Texture2D> {
+        self.texture.as_ref()
+    }
+
+    // Texture shown on the model. Only used by shaders that sample
+    // `u_texture`.
+    pub fn set_texture(&mut self, texture: Option<Texture2D>) {
+        self.texture = texture;
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    // Updates the vertex colors, visible when the gradient shaders are used
+    pub fn set_corner_colors(&mut self, corner_colors: [Color; 3]) {
+        self.mesh.set_vertices(&triangle_vertices(corner_colors));
+    }
+
+    // Model transform of the triangle
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    // Jumps to the transform without blending from the previous one
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.previous_transform = transform;
+    }
+
+    // Degrees per second the triangle turns around Z
+    pub fn set_spin(&mut self, degrees_per_second: f32) {
+        self.spin = degrees_per_second.to_radians();
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn camera(&self) -> Option<&Camera> {
+        self.camera.as_ref()
+    }
+
+    pub fn camera_mut(&mut self) -> Option<&mut Camera> {
+        self.camera.as_mut()
+    }
+
+    // Takes the aspect ratio of the current viewport
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera.map(|mut camera| {
+            camera.set_aspect_ratio(self.viewport.width, self.viewport.height);
+            camera
+        });
+    }
+
+    pub fn wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    // Draws only the triangle edges
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
+    // Projection * view * model of the triangle, `alpha` of the way from the
+    // previous update to the current one
+    pub fn mvp(&self, alpha: f32) -> Mat4 {
+        self.view_projection() * self.model_matrix(alpha)
+    }
+
+    fn view_projection(&self) -> Mat4 {
+        self.camera
+            .map_or(Mat4::IDENTITY, |camera| camera.view_projection())
+    }
+
+    pub fn model_matrix(&self, alpha: f32) -> Mat4 {
+        self.previous_transform
+            .lerp(&self.transform, alpha)
+            .matrix()
+    }
+
+    // Advances the animation by one fixed step of `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        self.previous_transform = self.transform;
+        self.previous_time = self.time;
+
+        self.time += dt;
+        if self.spin != 0.0 {
+            self.transform.rotate(Quat::from_rotation_z(self.spin * dt));
+        }
+    }
+
+    // Fits the viewport to a new framebuffer size, letterboxed if the aspect
+    // ratio is kept. The bars keep the clear color.
+    pub fn resize(&mut self, width: i32, height: i32) {
+        self.viewport = Viewport::fit(width, height, self.aspect_ratio);
+        let Viewport {
+            x,
+            y,
+            width,
+            height,
+        } = self.viewport;
+        unsafe { gl::Viewport(x, y, width, height) };
+        if let Some(camera) = &mut self.camera {
+            camera.set_aspect_ratio(width, height);
+        }
+    }
+
+    // Rebuilds the program from its sources. On failure the current program
+    // stays in use.
+    pub fn reload_shaders(&mut self) -> Result<(), Error> {
+        self.shader_program = self.shader_sources.build()?;
+        Ok(())
+    }
+
+    // Reloads the shaders if any of the watched shader files changed, and
+    // the scene if any of its files did
+    pub fn hot_reload(&mut self) {
+        if let Some(scene) = &mut self.scene {
+            scene.hot_reload();
+        }
+        let Some(watcher) = &mut self.shader_watcher else {
+            return;
+        };
+        if !watcher.poll() {
+            return;
+        }
+
+        match self.reload_shaders() {
+            Ok(()) => println!("Shaders reloaded"),
+            Err(e) => eprintln!("Shader reload failed, keeping the last good program: {e}"),
+        }
+    }
+
+    // Clears the current framebuffer and draws the triangle into it.
+    // `alpha` blends between the last two updates, 1.0 draws the latest state.
+    pub fn render(&mut self, alpha: f32) {
+        let time = self.previous_time + (self.time - self.previous_time) * alpha;
+        let program = &self.shader_program;
+        program.set_uniform(U_COLOR, &self.color);
+        program.set_uniform(U_MVP, &self.mvp(alpha));
+        program.set_uniform(U_MODEL, &self.model_matrix(alpha));
+        program.set_uniform(U_TIME, &time);
+        program.set_uniform(U_TEXTURE, &(TEXTURE_UNIT as i32));
+
+        let polygon_mode = if self.wireframe { gl::LINE } else { gl::FILL };
+        unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, polygon_mode) };
+
+        if let Some(scene) = &self.scene {
+            // Depth tested like a model, but objects at the same depth are
+            // drawn over each other in order
+            gl_clear_color(scene.clear_color().unwrap_or(self.clear_color));
+            unsafe {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::DepthFunc(gl::LEQUAL);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            }
+            scene.draw(&self.view_projection(), &self.model_matrix(alpha), time);
+            unsafe {
+                gl::DepthFunc(gl::LESS);
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            return;
+        }
+        let Some(model) = &self.model else {
+            render_frame(
+                &mut self.backend,
+                &self.clear_color,
+                &self.shader_program,
+                &self.mesh,
+            );
+            return;
+        };
+        // Models overlap themselves, so they need the depth test. It is
+        // turned off again for whatever is drawn on top, like text.
+        gl_clear_color(self.clear_color);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+        self.shader_program.bind();
+        match &self.texture {
+            Some(texture) => {
+                // Images may have transparent parts
+                texture.bind(TEXTURE_UNIT);
+                unsafe {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                }
+                model.draw();
+                texture.unbind(TEXTURE_UNIT);
+                unsafe { gl::Disable(gl::BLEND) };
+            }
+            None => model.draw(),
+        }
+        unsafe { gl::Disable(gl::DEPTH_TEST) };
+    }
+}
+
+// Draws the same frame as `Renderer::render` with the CPU rasterizer, no GL
+// context needed. It only draws the triangle, options that would change
+// anything else are rejected rather than ignored.
+pub fn render_software(config: &Config) -> Result<SoftwareBackend, Error> {
+    let unsupported = [
+        ("--model", config.model.is_some()),
+        ("--scene", config.scene.is_some()),
+        ("--texture", config.texture.is_some()),
+        ("--effects", !config.effects.is_empty()),
+        ("--vert", config.vert_shader.is_some()),
+        ("--frag", config.frag_shader.is_some()),
+    ];
+    if let Some((option, _)) = unsupported.iter().find(|(_, set)| *set) {
+        return Err(Error::Args(format!(
+            "{option} is not supported with --software"
+        )));
+    }
+    let mut backend = SoftwareBackend::new(config.width, config.height);
+    let program = if config.gradient {
+        backend.gradient_program()?
+    } else {
+        backend.flat_program(TRIANGLE_COLOR)?
+    };
+    let mesh = backend.upload_mesh(&triangle_vertices(config.corner_colors));
+    render_frame(&mut backend, &config.clear_color, &program, &mesh);
+    Ok(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Effect;
+
+    #[test]
+    fn software_rejects_gl_only_options() {
+        let config = Config {
+            width: 8,
+            height: 8,
+            ..Config::default()
+        };
+        assert!(render_software(&config).is_ok());
+        let config = Config {
+            effects: vec![Effect::Invert],
+            ..config
+        };
+        assert_eq!(
+            render_software(&config).err().unwrap().to_string(),
+            "--effects is not supported with --software"
+        );
+    }
+}
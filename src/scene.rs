@@ -0,0 +1,466 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::shader::{U_COLOR, U_MODEL, U_MVP, U_TIME};
+use crate::{
+    Camera, Color, DEFAULT_FRAG_SHADER, DEFAULT_VERT_SHADER, Error, FileWatcher,
+    GRADIENT_FRAG_SHADER, GRADIENT_VERT_SHADER, MESH_FRAG_SHADER, MESH_VERT_SHADER, Mat4, Mesh,
+    MeshData, MeshVertex, Quat, ShaderProgram, ShaderSources, TRIANGLE_VERTICES, Topology,
+    Transform, Vec3,
+};
+
+const CIRCLE_RADIUS: f32 = 0.5;
+const CIRCLE_SEGMENTS: u32 = 32;
+// Scenes are laid out from -1 to 1 vertically, the width follows the window
+const SCENE_VIEW_HEIGHT: f32 = 2.0;
+// Far enough back to keep objects between z = -10 and 10 in front of it
+const SCENE_CAMERA_Z: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    // Three `vertices`, the default triangle without them
+    Triangle,
+    // `size` wide and high, 1 x 1 by default
+    Quad,
+    // `radius` with `segments` sides
+    Circle,
+    // Convex outline through three or more `vertices`
+    Polygon,
+    // OBJ or glTF file at `path`
+    Mesh,
+}
+
+// Built-in shaders an object can be drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShaderKind {
+    // The object's `color`
+    Flat,
+    // The per-vertex `colors`
+    Gradient,
+    // The object's `color` under a fixed directional light
+    Lit,
+}
+
+// A number for all three axes, or one per axis
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Components {
+    Uniform(f32),
+    Xyz([f32; 3]),
+}
+
+// One entry of the `objects` list of a scene file. Which keys apply depends
+// on the `shape`; positions are [x, y] or [x, y, z].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ObjectData {
+    pub shape: Shape,
+    #[serde(default)]
+    pub vertices: Vec<Vec<f32>>,
+    pub size: Option<[f32; 2]>,
+    pub radius: Option<f32>,
+    pub segments: Option<u32>,
+    pub path: Option<PathBuf>,
+    #[serde(default = "white")]
+    pub color: Color,
+    // One per vertex: 3 for a triangle, 4 for a quad (counterclockwise from
+    // the bottom-left corner), center and rim for a circle
+    #[serde(default)]
+    pub colors: Vec<Color>,
+    #[serde(default)]
+    pub position: Vec<f32>,
+    // Degrees around Z, or [x, y, z] Euler angles in degrees
+    pub rotation: Option<Components>,
+    pub scale: Option<Components>,
+    // Flat by default, gradient with `colors`, lit for meshes
+    pub shader: Option<ShaderKind>,
+    // GLSL files replacing the stages of the shader, hot-reloaded
+    pub vert: Option<PathBuf>,
+    pub frag: Option<PathBuf>,
+}
+
+fn white() -> Color {
+    Color::WHITE
+}
+
+impl ObjectData {
+    // Vertices in object space. Relative mesh paths start at `base_dir`.
+    pub fn mesh_data(&self, base_dir: &Path) -> Result<MeshData, String> {
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|v| vec3(v).ok_or_else(|| format!("invalid vertex {v:?}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut data = match self.shape {
+            Shape::Triangle => {
+                let vertices = match vertices.len() {
+                    0 => TRIANGLE_VERTICES
+                        .iter()
+                        .map(|vertex| Vec3::from(vertex.position))
+                        .collect(),
+                    3 => vertices,
+                    n => return Err(format!("a triangle has 3 vertices, got {n}")),
+                };
+                fan(&vertices)
+            }
+            Shape::Quad => {
+                let [width, height] = self.size.unwrap_or([1.0, 1.0]);
+                let mut data = MeshData::quad();
+                for vertex in &mut data.vertices {
+                    vertex.position[0] *= width;
+                    vertex.position[1] *= height;
+                }
+                data
+            }
+            Shape::Circle => {
+                let radius = self.radius.unwrap_or(CIRCLE_RADIUS);
+                let segments = self.segments.unwrap_or(CIRCLE_SEGMENTS);
+                if segments < 3 {
+                    return Err(format!("a circle needs 3 or more segments, got {segments}"));
+                }
+                circle(radius, segments)
+            }
+            Shape::Polygon => {
+                if vertices.len() < 3 {
+                    return Err(format!(
+                        "a polygon needs 3 or more vertices, got {}",
+                        vertices.len()
+                    ));
+                }
+                fan(&vertices)
+            }
+            Shape::Mesh => {
+                let path = self.path.as_ref().ok_or("a mesh needs a path")?;
+                MeshData::load(&base_dir.join(path)).map_err(|e| e.to_string())?
+            }
+        };
+        self.apply_colors(&mut data)?;
+        Ok(data)
+    }
+
+    fn apply_colors(&self, data: &mut MeshData) -> Result<(), String> {
+        if self.colors.is_empty() {
+            return Ok(());
+        }
+        let colors = match (self.shape, self.colors.as_slice()) {
+            (Shape::Mesh, _) => return Err("meshes don't take vertex colors".to_string()),
+            // Center first, the rim shares the second color
+            (Shape::Circle, &[center, rim]) => {
+                let mut colors = vec![rim; data.vertices.len()];
+                colors[0] = center;
+                colors
+            }
+            (Shape::Circle, colors) => {
+                return Err(format!("a circle takes 2 colors, got {}", colors.len()));
+            }
+            (_, colors) if colors.len() == data.vertices.len() => colors.to_vec(),
+            (_, colors) => {
+                return Err(format!(
+                    "expected {} colors, one per vertex, got {}",
+                    data.vertices.len(),
+                    colors.len()
+                ));
+            }
+        };
+        for (vertex, color) in data.vertices.iter_mut().zip(colors) {
+            vertex.color = color.to_array();
+        }
+        Ok(())
+    }
+
+    pub fn transform(&self) -> Result<Transform, String> {
+        let translation = if self.position.is_empty() {
+            Vec3::ZERO
+        } else {
+            vec3(&self.position).ok_or_else(|| format!("invalid position {:?}", self.position))?
+        };
+        let rotation = match self.rotation {
+            None => Quat::IDENTITY,
+            Some(Components::Uniform(z)) => Quat::from_rotation_z(z.to_radians()),
+            Some(Components::Xyz([x, y, z])) => {
+                Quat::from_euler(y.to_radians(), x.to_radians(), z.to_radians())
+            }
+        };
+        let scale = match self.scale {
+            None => Vec3::ONE,
+            Some(Components::Uniform(s)) => Vec3::splat(s),
+            Some(Components::Xyz(s)) => Vec3::from(s),
+        };
+        Ok(Transform::from_translation(translation)
+            .with_rotation(rotation)
+            .with_scale(scale))
+    }
+
+    pub fn shader_kind(&self) -> ShaderKind {
+        self.shader.unwrap_or(match self.shape {
+            Shape::Mesh => ShaderKind::Lit,
+            _ if !self.colors.is_empty() => ShaderKind::Gradient,
+            _ => ShaderKind::Flat,
+        })
+    }
+
+    // Relative shader paths start at `base_dir`
+    pub fn shader_sources(&self, base_dir: &Path) -> ShaderSources {
+        let (vert, frag) = match self.shader_kind() {
+            ShaderKind::Flat => (DEFAULT_VERT_SHADER, DEFAULT_FRAG_SHADER),
+            ShaderKind::Gradient => (GRADIENT_VERT_SHADER, GRADIENT_FRAG_SHADER),
+            ShaderKind::Lit => (MESH_VERT_SHADER, MESH_FRAG_SHADER),
+        };
+        ShaderSources::new(
+            self.vert.as_ref().map(|path| base_dir.join(path)),
+            self.frag.as_ref().map(|path| base_dir.join(path)),
+        )
+        .with_defaults(vert, frag)
+    }
+}
+
+// [x, y] or [x, y, z]
+fn vec3(components: &[f32]) -> Option<Vec3> {
+    match *components {
+        [x, y] => Some(Vec3::new(x, y, 0.0)),
+        [x, y, z] => Some(Vec3::new(x, y, z)),
+        _ => None,
+    }
+}
+
+// Convex outline in the XY plane, split into triangles around the first
+// vertex
+fn fan(outline: &[Vec3]) -> MeshData {
+    MeshData {
+        vertices: outline
+            .iter()
+            .map(|position| MeshVertex {
+                position: position.to_array(),
+                normal: [0.0, 0.0, 1.0],
+                ..MeshVertex::default()
+            })
+            .collect(),
+        indices: (1..outline.len() as u32 - 1)
+            .flat_map(|i| [0, i, i + 1])
+            .collect(),
+        topology: Topology::Triangles,
+    }
+}
+
+// Center vertex first, then the rim counterclockwise from +X
+fn circle(radius: f32, segments: u32) -> MeshData {
+    let rim = (0..segments).map(|i| {
+        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+        Vec3::new(angle.cos(), angle.sin(), 0.0) * radius
+    });
+    let mut data = fan(&std::iter::once(Vec3::ZERO).chain(rim).collect::<Vec<_>>());
+    // Close the last gap between the end and the start of the rim
+    data.indices.extend([0, segments, 1]);
+    data
+}
+
+// Contents of a scene file, e.g.
+//
+//   clear_color = "#101018"
+//
+//   [[objects]]
+//   shape = "circle"
+//   radius = 0.3
+//   colors = ["white", "orange"]
+//   position = [-0.5, 0.2]
+//
+//   [[objects]]
+//   shape = "mesh"
+//   path = "cube.obj"
+//   scale = 0.4
+//   rotation = [30, 45, 0]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SceneData {
+    // Overrides the `clear_color` of the config
+    pub clear_color: Option<Color>,
+    // Drawn in order, later objects over earlier ones at the same depth
+    pub objects: Vec<ObjectData>,
+}
+
+impl SceneData {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| Error::Toml {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+// An object of a `Scene`, ready to draw
+struct SceneObject {
+    mesh: Mesh,
+    program: ShaderProgram,
+    color: Color,
+    transform: Transform,
+}
+
+// The objects of a scene file on the GPU. The file, and the shader and mesh
+// files it names, are watched and the scene is rebuilt when one of them
+// changes. Needs a current GL context.
+pub struct Scene {
+    path: PathBuf,
+    clear_color: Option<Color>,
+    objects: Vec<SceneObject>,
+    watcher: FileWatcher,
+}
+
+impl Scene {
+    // Paths in the file are relative to its directory
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = SceneData::load(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let scene_error = |i: usize, message: String| Error::Scene {
+            path: path.to_path_buf(),
+            message: format!("objects[{i}]: {message}"),
+        };
+
+        let mut files = vec![path.to_path_buf()];
+        let mut objects = Vec::new();
+        for (i, object) in data.objects.iter().enumerate() {
+            let mesh_data = object
+                .mesh_data(base_dir)
+                .map_err(|message| scene_error(i, message))?;
+            let transform = object
+                .transform()
+                .map_err(|message| scene_error(i, message))?;
+            let sources = object.shader_sources(base_dir);
+            let program = sources.build().map_err(|e| scene_error(i, e.to_string()))?;
+
+            files.extend(sources.files().into_iter().map(Path::to_path_buf));
+            files.extend(object.path.as_ref().map(|mesh| base_dir.join(mesh)));
+            let mesh = Mesh::new(&mesh_data).map_err(|e| scene_error(i, e.to_string()))?;
+            objects.push(SceneObject {
+                mesh,
+                program,
+                color: object.color,
+                transform,
+            });
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            clear_color: data.clear_color,
+            objects,
+            watcher: FileWatcher::new(&files),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn clear_color(&self) -> Option<Color> {
+        self.clear_color
+    }
+
+    // Orthographic camera looking down -Z, so shapes keep their proportions
+    // and meshes face the light
+    pub fn camera(&self) -> Camera {
+        Camera::orthographic(SCENE_VIEW_HEIGHT)
+            .with_position(Vec3::Z * SCENE_CAMERA_Z)
+            .with_clip_planes(0.01, SCENE_CAMERA_Z * 2.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    // Reloads the scene if any of its files changed. On failure the current
+    // scene stays.
+    pub fn hot_reload(&mut self) {
+        if !self.watcher.poll() {
+            return;
+        }
+        match Self::load(&self.path) {
+            Ok(scene) => {
+                *self = scene;
+                println!("Scene reloaded");
+            }
+            Err(e) => eprintln!("Scene reload failed, keeping the last good scene: {e}"),
+        }
+    }
+
+    // Draws every object with `view_projection * model` and its own
+    // transform on top
+    pub fn draw(&self, view_projection: &Mat4, model: &Mat4, time: f32) {
+        for object in &self.objects {
+            let model = *model * object.transform.matrix();
+            let program = &object.program;
+            program.set_uniform(U_COLOR, &object.color);
+            program.set_uniform(U_MVP, &(*view_projection * model));
+            program.set_uniform(U_MODEL, &model);
+            program.set_uniform(U_TIME, &time);
+            program.bind();
+            object.mesh.draw();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenes_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenes")
+    }
+
+    // Mesh data of a single object, mesh paths relative to the working directory
+    fn object(body: &str) -> Result<MeshData, String> {
+        let object: ObjectData = toml::from_str(body).map_err(|e| e.to_string())?;
+        object.mesh_data(Path::new(""))
+    }
+
+    #[test]
+    fn scene_object_defaults() {
+        let data = SceneData::load(&scenes_dir().join("shapes.toml")).unwrap();
+        let kinds: Vec<_> = data.objects.iter().map(ObjectData::shader_kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ShaderKind::Gradient,
+                ShaderKind::Gradient,
+                ShaderKind::Gradient,
+                ShaderKind::Flat,
+                ShaderKind::Lit
+            ]
+        );
+        // Center plus rim, a triangle around the center per segment
+        let circle = data.objects[2].mesh_data(&scenes_dir()).unwrap();
+        assert_eq!(circle.vertices.len(), 49);
+        assert_eq!(circle.indices.len(), 48 * 3);
+        let polygon = data.objects[3].mesh_data(&scenes_dir()).unwrap();
+        assert_eq!(polygon.indices.len(), 3 * 3);
+    }
+
+    #[test]
+    fn invalid_objects() {
+        assert!(object("shape = \"polygon\"\nvertices = [[0, 0], [1, 0]]").is_err());
+        assert_eq!(
+            object("shape = \"triangle\"\nvertices = [[0, 0], [1, 0], [0, 1, 2, 3]]").err(),
+            Some("invalid vertex [0.0, 1.0, 2.0, 3.0]".to_string())
+        );
+        assert!(object("shape = \"circle\"\ncolors = [\"red\"]").is_err());
+        assert!(object("shape = \"mesh\"").is_err());
+        assert!(object("shape = \"hexagon\"").is_err());
+        assert!(object("shape = \"quad\"\nsize = [1, 2]\ncolour = \"red\"").is_err());
+        assert!(object("shape = \"quad\"\nsize = [1, 2]\ncolor = \"red\"").is_ok());
+    }
+
+    #[test]
+    fn invalid_scene_file() {
+        let path = scenes_dir().join("missing.toml");
+        assert!(matches!(SceneData::load(&path), Err(Error::Io { .. })));
+    }
+}
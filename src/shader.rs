@@ -0,0 +1,712 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::{Color, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl ShaderStage {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "Vertex"),
+            ShaderStage::Fragment => write!(f, "Fragment"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    // `lines` holds the source line numbers the driver log points at
+    Compile {
+        stage: ShaderStage,
+        log: String,
+        lines: Vec<u32>,
+    },
+    Link {
+        log: String,
+    },
+}
+
+impl ShaderError {
+    pub fn log(&self) -> &str {
+        match self {
+            ShaderError::Compile { log, .. } | ShaderError::Link { log } => log,
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Compile { stage, log, .. } => {
+                write!(f, "{stage} Shader Compile Error: {}", log.trim_end())
+            }
+            ShaderError::Link { log } => write!(f, "Program Link Error: {}", log.trim_end()),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+// Extracts the source line numbers from a driver info log. Understands the
+// Mesa `0:12(5): error`, NVIDIA `0(12) : error` and `ERROR: 0:12: ...` styles.
+pub fn parse_log_line_numbers(log: &str) -> Vec<u32> {
+    let mut lines = Vec::new();
+    for entry in log.lines() {
+        let entry = entry.trim_start();
+        let entry = entry
+            .strip_prefix("ERROR:")
+            .or_else(|| entry.strip_prefix("WARNING:"))
+            .unwrap_or(entry)
+            .trim_start();
+
+        // Skip the source string index
+        let rest = entry.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == entry.len() {
+            continue;
+        }
+        let Some(rest) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('(')) else {
+            continue;
+        };
+
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if let Ok(line) = digits.parse()
+            && !lines.contains(&line)
+        {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+// Receives the non-empty info logs of shaders and programs that compiled or
+// linked fine, which is where drivers put their warnings. `None` is the link step.
+pub type ShaderLogHook = fn(Option<ShaderStage>, &str);
+
+static LOG_HOOK: RwLock<ShaderLogHook> = RwLock::new(default_log_hook);
+
+fn default_log_hook(stage: Option<ShaderStage>, log: &str) {
+    match stage {
+        Some(stage) => eprintln!("{stage} Shader Compile Warning: {}", log.trim_end()),
+        None => eprintln!("Program Link Warning: {}", log.trim_end()),
+    }
+}
+
+pub fn set_shader_log_hook(hook: ShaderLogHook) {
+    *LOG_HOOK.write().unwrap() = hook;
+}
+
+fn report_warnings(stage: Option<ShaderStage>, log: &str) {
+    if !log.trim().is_empty() {
+        (LOG_HOOK.read().unwrap())(stage, log);
+    }
+}
+
+// Reads the whole info log, sized with `INFO_LOG_LENGTH` so long logs are not cut off
+unsafe fn read_info_log(
+    id: u32,
+    get_iv: unsafe fn(gl::types::GLuint, gl::types::GLenum, *mut gl::types::GLint),
+    get_log: unsafe fn(
+        gl::types::GLuint,
+        gl::types::GLsizei,
+        *mut gl::types::GLsizei,
+        *mut gl::types::GLchar,
+    ),
+) -> String {
+    let mut capacity = 0_i32;
+    unsafe { get_iv(id, gl::INFO_LOG_LENGTH, &mut capacity) };
+    if capacity <= 0 {
+        return String::new();
+    }
+
+    let mut v: Vec<u8> = Vec::with_capacity(capacity as usize);
+    let mut log_len = 0_i32;
+    unsafe {
+        get_log(id, capacity, &mut log_len, v.as_mut_ptr().cast());
+        v.set_len(log_len.clamp(0, capacity) as usize);
+    }
+    String::from_utf8_lossy(&v).into_owned()
+}
+
+pub struct Shader {
+    id: u32,
+    stage: ShaderStage,
+}
+
+impl Shader {
+    pub fn new(stage: ShaderStage, source: &str) -> Result<Self, ShaderError> {
+        let shader = Self {
+            id: unsafe { gl::CreateShader(stage.gl_enum()) },
+            stage,
+        };
+
+        unsafe {
+            gl::ShaderSource(
+                shader.id,
+                1,
+                &source.as_bytes().as_ptr().cast(),
+                &source.len().try_into().unwrap(),
+            );
+            gl::CompileShader(shader.id);
+
+            let mut success = 0;
+            gl::GetShaderiv(shader.id, gl::COMPILE_STATUS, &mut success);
+            if success == 0 {
+                let log = shader.info_log();
+                return Err(ShaderError::Compile {
+                    stage,
+                    lines: parse_log_line_numbers(&log),
+                    log,
+                });
+            }
+        }
+
+        report_warnings(Some(stage), &shader.info_log());
+        Ok(shader)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn stage(&self) -> ShaderStage {
+        self.stage
+    }
+
+    pub fn info_log(&self) -> String {
+        unsafe { read_info_log(self.id, gl::GetShaderiv, gl::GetShaderInfoLog) }
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteShader(self.id) }
+    }
+}
+
+// Column-major 4x4 matrix, the layout `glUniformMatrix4fv` expects
+pub type Mat4Array = [[f32; 4]; 4];
+
+// A value that can be uploaded with `ShaderProgram::set_uniform`
+pub trait Uniform {
+    // Sets the uniform at `location` of the program in use
+    fn set(&self, location: i32);
+}
+
+impl Uniform for f32 {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform1f(location, *self) }
+    }
+}
+
+impl Uniform for i32 {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self) }
+    }
+}
+
+impl Uniform for u32 {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform1ui(location, *self) }
+    }
+}
+
+impl Uniform for bool {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform1i(location, *self as i32) }
+    }
+}
+
+impl Uniform for [f32; 2] {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform2fv(location, 1, self.as_ptr()) }
+    }
+}
+
+impl Uniform for [f32; 3] {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform3fv(location, 1, self.as_ptr()) }
+    }
+}
+
+impl Uniform for [f32; 4] {
+    fn set(&self, location: i32) {
+        unsafe { gl::Uniform4fv(location, 1, self.as_ptr()) }
+    }
+}
+
+impl Uniform for Color {
+    fn set(&self, location: i32) {
+        self.to_array().set(location)
+    }
+}
+
+impl Uniform for Mat4Array {
+    fn set(&self, location: i32) {
+        unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, self.as_ptr().cast()) }
+    }
+}
+
+pub struct ShaderProgram {
+    id: u32,
+    // Looked up once per name, -1 for uniforms the program does not use
+    uniform_locations: RefCell<HashMap<String, i32>>,
+}
+
+impl ShaderProgram {
+    pub fn from_sources(vert: &str, frag: &str) -> Result<Self, ShaderError> {
+        let vertex_shader = Shader::new(ShaderStage::Vertex, vert)?;
+        let fragment_shader = Shader::new(ShaderStage::Fragment, frag)?;
+        Self::link(&[&vertex_shader, &fragment_shader])
+    }
+
+    pub fn link(shaders: &[&Shader]) -> Result<Self, ShaderError> {
+        let program = Self {
+            id: unsafe { gl::CreateProgram() },
+            uniform_locations: RefCell::default(),
+        };
+
+        unsafe {
+            for shader in shaders {
+                gl::AttachShader(program.id, shader.id());
+            }
+            gl::LinkProgram(program.id);
+            for shader in shaders {
+                gl::DetachShader(program.id, shader.id());
+            }
+
+            let mut success = 0;
+            gl::GetProgramiv(program.id, gl::LINK_STATUS, &mut success);
+            if success == 0 {
+                return Err(ShaderError::Link {
+                    log: program.info_log(),
+                });
+            }
+        }
+
+        report_warnings(None, &program.info_log());
+        Ok(program)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::UseProgram(self.id) }
+    }
+
+    pub fn info_log(&self) -> String {
+        unsafe { read_info_log(self.id, gl::GetProgramiv, gl::GetProgramInfoLog) }
+    }
+
+    // -1 if the program has no active uniform with that name
+    pub fn uniform_location(&self, name: &str) -> i32 {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return location;
+        }
+
+        let location = match CString::new(name) {
+            Ok(c_name) => unsafe { gl::GetUniformLocation(self.id, c_name.as_ptr()) },
+            Err(_) => -1,
+        };
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.to_string(), location);
+        location
+    }
+
+    // Binds the program and sets the uniform. Uniforms the program does not
+    // have (or the compiler optimized out) are ignored.
+    pub fn set_uniform<T: Uniform + ?Sized>(&self, name: &str, value: &T) {
+        let location = self.uniform_location(name);
+        if location < 0 {
+            return;
+        }
+        self.bind();
+        value.set(location);
+    }
+}
+
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.id) }
+    }
+}
+
+// Vertex and fragment sources of a program. Stages without a file fall back
+// to the default shaders.
+pub struct ShaderSources {
+    vert: Option<PathBuf>,
+    frag: Option<PathBuf>,
+    default_vert: &'static str,
+    default_frag: &'static str,
+}
+
+impl ShaderSources {
+    pub fn new(vert: Option<PathBuf>, frag: Option<PathBuf>) -> Self {
+        Self {
+            vert,
+            frag,
+            default_vert: DEFAULT_VERT_SHADER,
+            default_frag: DEFAULT_FRAG_SHADER,
+        }
+    }
+
+    // Replaces the sources used for stages without a file
+    pub fn with_defaults(mut self, vert: &'static str, frag: &'static str) -> Self {
+        self.default_vert = vert;
+        self.default_frag = frag;
+        self
+    }
+
+    pub fn files(&self) -> Vec<&Path> {
+        self.vert
+            .iter()
+            .chain(&self.frag)
+            .map(PathBuf::as_path)
+            .collect()
+    }
+
+    pub fn build(&self) -> Result<ShaderProgram, Error> {
+        let vert = match &self.vert {
+            Some(path) => read_source(path)?,
+            None => self.default_vert.to_string(),
+        };
+        let frag = match &self.frag {
+            Some(path) => read_source(path)?,
+            None => self.default_frag.to_string(),
+        };
+        Ok(ShaderProgram::from_sources(&vert, &frag)?)
+    }
+}
+
+fn read_source(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// Uniforms every program gets from the renderer each frame
+pub const U_COLOR: &str = "u_color";
+// Projection * view * model
+pub const U_MVP: &str = "u_mvp";
+// Model matrix alone, for lighting in world space
+pub const U_MODEL: &str = "u_model";
+pub const U_TIME: &str = "u_time";
+// Texture unit the renderer binds its texture to
+pub const U_TEXTURE: &str = "u_texture";
+// Post-processing passes: the frame so far and the size of one of its pixels
+// in texture coordinates
+pub const U_SCENE: &str = "u_scene";
+pub const U_TEXEL_SIZE: &str = "u_texel_size";
+
+pub const DEFAULT_VERT_SHADER: &str = "#version 330 core
+    layout (location = 0) in vec3 position;
+    uniform mat4 u_mvp;
+    void main()
+    {
+        gl_Position = u_mvp * vec4(position, 1.0);
+    }";
+
+pub const DEFAULT_FRAG_SHADER: &str = "#version 330 core
+    uniform vec4 u_color;
+    out vec4 Color;
+    void main()
+    {
+        Color = u_color;
+    }";
+
+// Interpolates the per-vertex color of a `ColorVertex` mesh
+pub const GRADIENT_VERT_SHADER: &str = "#version 330 core
+    layout (location = 0) in vec3 position;
+    layout (location = 1) in vec4 color;
+    uniform mat4 u_mvp;
+    out vec4 v_color;
+    void main()
+    {
+        gl_Position = u_mvp * vec4(position, 1.0);
+        v_color = color;
+    }";
+
+pub const GRADIENT_FRAG_SHADER: &str = "#version 330 core
+    in vec4 v_color;
+    out vec4 Color;
+    void main()
+    {
+        Color = v_color;
+    }";
+
+// Lit by a fixed directional light, for `MeshVertex` models. The normal
+// matrix assumes the model transform scales uniformly.
+pub const MESH_VERT_SHADER: &str = "#version 330 core
+    layout (location = 0) in vec3 position;
+    layout (location = 2) in vec3 normal;
+    layout (location = 3) in vec2 uv;
+    uniform mat4 u_mvp;
+    uniform mat4 u_model;
+    out vec3 v_normal;
+    out vec2 v_uv;
+    void main()
+    {
+        gl_Position = u_mvp * vec4(position, 1.0);
+        v_normal = mat3(u_model) * normal;
+        v_uv = uv;
+    }";
+
+pub const MESH_FRAG_SHADER: &str = "#version 330 core
+    in vec3 v_normal;
+    in vec2 v_uv;
+    uniform vec4 u_color;
+    out vec4 Color;
+    void main()
+    {
+        vec3 light = normalize(vec3(0.4, 0.7, 1.0));
+        float diffuse = max(dot(normalize(v_normal), light), 0.0);
+        Color = vec4(u_color.rgb * (0.25 + 0.75 * diffuse), u_color.a);
+    }";
+
+// Unlit `MeshVertex` mesh showing the texture bound to `u_texture`
+pub const TEXTURE_VERT_SHADER: &str = "#version 330 core
+    layout (location = 0) in vec3 position;
+    layout (location = 3) in vec2 uv;
+    uniform mat4 u_mvp;
+    out vec2 v_uv;
+    void main()
+    {
+        gl_Position = u_mvp * vec4(position, 1.0);
+        v_uv = uv;
+    }";
+
+pub const TEXTURE_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_texture;
+    out vec4 Color;
+    void main()
+    {
+        Color = texture(u_texture, v_uv);
+    }";
+
+// Glyph quads of a `TextVertex` batch, tinted by the vertex color and
+// masked by the coverage in the red channel of the font atlas
+pub const TEXT_VERT_SHADER: &str = "#version 330 core
+    layout (location = 0) in vec3 position;
+    layout (location = 1) in vec4 color;
+    layout (location = 3) in vec2 uv;
+    uniform mat4 u_mvp;
+    out vec4 v_color;
+    out vec2 v_uv;
+    void main()
+    {
+        gl_Position = u_mvp * vec4(position, 1.0);
+        v_color = color;
+        v_uv = uv;
+    }";
+
+pub const TEXT_FRAG_SHADER: &str = "#version 330 core
+    in vec4 v_color;
+    in vec2 v_uv;
+    uniform sampler2D u_atlas;
+    out vec4 Color;
+    void main()
+    {
+        Color = vec4(v_color.rgb, v_color.a * texture(u_atlas, v_uv).r);
+    }";
+
+// Full-screen triangle generated from the vertex index, no vertex buffer
+// needed. Shared by all post-processing passes, which sample `u_scene` at
+// `v_uv`.
+pub const POST_VERT_SHADER: &str = "#version 330 core
+    out vec2 v_uv;
+    void main()
+    {
+        vec2 corner = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+        v_uv = corner;
+        gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+    }";
+
+// Passes the frame through unchanged, a starting point for custom effects
+pub const POST_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    out vec4 Color;
+    void main()
+    {
+        Color = texture(u_scene, v_uv);
+    }";
+
+// Rec. 709 luminance
+pub const GRAYSCALE_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    out vec4 Color;
+    void main()
+    {
+        vec4 color = texture(u_scene, v_uv);
+        float luma = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+        Color = vec4(vec3(luma), color.a);
+    }";
+
+pub const INVERT_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    out vec4 Color;
+    void main()
+    {
+        vec4 color = texture(u_scene, v_uv);
+        Color = vec4(1.0 - color.rgb, color.a);
+    }";
+
+// 3x3 Gaussian kernel
+pub const BLUR_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    uniform vec2 u_texel_size;
+    out vec4 Color;
+    void main()
+    {
+        vec4 sum = vec4(0.0);
+        for (int y = -1; y <= 1; y++) {
+            for (int x = -1; x <= 1; x++) {
+                float weight = float((2 - abs(x)) * (2 - abs(y)));
+                sum += weight * texture(u_scene, v_uv + vec2(x, y) * u_texel_size);
+            }
+        }
+        Color = sum / 16.0;
+    }";
+
+// Center minus its four neighbours, added back on top of the center
+pub const SHARPEN_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    uniform vec2 u_texel_size;
+    out vec4 Color;
+    void main()
+    {
+        vec4 center = texture(u_scene, v_uv);
+        vec4 neighbours = texture(u_scene, v_uv + vec2(u_texel_size.x, 0.0))
+            + texture(u_scene, v_uv - vec2(u_texel_size.x, 0.0))
+            + texture(u_scene, v_uv + vec2(0.0, u_texel_size.y))
+            + texture(u_scene, v_uv - vec2(0.0, u_texel_size.y));
+        Color = clamp(5.0 * center - neighbours, 0.0, 1.0);
+    }";
+
+// Darkens towards the corners
+pub const VIGNETTE_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    out vec4 Color;
+    void main()
+    {
+        vec4 color = texture(u_scene, v_uv);
+        float distance = length(v_uv - 0.5);
+        Color = vec4(color.rgb * (1.0 - smoothstep(0.35, 0.8, distance)), color.a);
+    }";
+
+// Fast approximate anti-aliasing: blurs along the edges found in the
+// luminance of the frame
+pub const FXAA_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    uniform vec2 u_texel_size;
+    out vec4 Color;
+    const float REDUCE_MIN = 1.0 / 128.0;
+    const float REDUCE_MUL = 1.0 / 8.0;
+    const float SPAN_MAX = 8.0;
+    const vec3 LUMA = vec3(0.299, 0.587, 0.114);
+    float luma(vec2 offset)
+    {
+        return dot(texture(u_scene, v_uv + offset * u_texel_size).rgb, LUMA);
+    }
+    void main()
+    {
+        vec4 center = texture(u_scene, v_uv);
+        float m = dot(center.rgb, LUMA);
+        float nw = luma(vec2(-1.0, 1.0));
+        float ne = luma(vec2(1.0, 1.0));
+        float sw = luma(vec2(-1.0, -1.0));
+        float se = luma(vec2(1.0, -1.0));
+        float luma_min = min(m, min(min(nw, ne), min(sw, se)));
+        float luma_max = max(m, max(max(nw, ne), max(sw, se)));
+
+        vec2 dir = vec2(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+        float reduce = max((nw + ne + sw + se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+        float scale = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+        dir = clamp(dir * scale, -SPAN_MAX, SPAN_MAX) * u_texel_size;
+
+        vec3 a = 0.5 * (texture(u_scene, v_uv - dir / 6.0).rgb
+            + texture(u_scene, v_uv + dir / 6.0).rgb);
+        vec3 b = 0.5 * a + 0.25 * (texture(u_scene, v_uv - dir * 0.5).rgb
+            + texture(u_scene, v_uv + dir * 0.5).rgb);
+        float luma_b = dot(b, LUMA);
+        Color = vec4(luma_b < luma_min || luma_b > luma_max ? a : b, center.a);
+    }";
+
+// ACES filmic curve (Narkowicz' fit). The frame is only 8 bits per channel,
+// so this shapes the contrast rather than compressing a high dynamic range.
+pub const TONEMAP_FRAG_SHADER: &str = "#version 330 core
+    in vec2 v_uv;
+    uniform sampler2D u_scene;
+    out vec4 Color;
+    void main()
+    {
+        vec4 color = texture(u_scene, v_uv);
+        vec3 x = color.rgb;
+        vec3 mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+        Color = vec4(clamp(mapped, 0.0, 1.0), color.a);
+    }";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesa_log_lines() {
+        let log = "0:12(5): error: `foo' undeclared\n\
+                   0:12(9): error: type mismatch\n\
+                   0:3(1): warning: extension `GL_ARB_foo' unsupported\n";
+        assert_eq!(parse_log_line_numbers(log), [12, 3]);
+    }
+
+    #[test]
+    fn nvidia_log_lines() {
+        let log = "0(7) : error C1008: undefined variable \"foo\"\n\
+                   0(21) : warning C7050: \"bar\" might be used before being initialized\n";
+        assert_eq!(parse_log_line_numbers(log), [7, 21]);
+    }
+
+    #[test]
+    fn prefixed_log_lines() {
+        // AMD, Intel on Windows and ANGLE
+        let log = "ERROR: 0:4: 'vec5' : no matching overloaded function found\n\
+                   WARNING: 0:9: 'x' : unused variable\n\
+                   ERROR: 2 compilation errors.  No code generated.\n";
+        assert_eq!(parse_log_line_numbers(log), [4, 9]);
+    }
+
+    #[test]
+    fn logs_without_line_numbers() {
+        assert!(parse_log_line_numbers("").is_empty());
+        let log = "error: linking with uncompiled shader\n  \n\
+                   Vertex info\n-----------\n0:(: malformed\n";
+        assert!(parse_log_line_numbers(log).is_empty());
+    }
+}
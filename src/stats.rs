@@ -0,0 +1,444 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::Error;
+
+// Frames the rolling statistics are computed over
+pub const ROLLING_FRAMES: usize = 240;
+// `GL_TIME_ELAPSED` queries in flight. Results are read a few frames late so
+// the CPU never waits for the GPU.
+const GPU_QUERIES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSample {
+    // Time since the previous frame started
+    pub frame: Duration,
+    // Time spent updating and submitting the frame
+    pub cpu: Duration,
+    // Time the GPU spent drawing it, once the query result is in
+    pub gpu: Option<Duration>,
+}
+
+// Min / avg / max / percentiles of a set of durations, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl Summary {
+    pub fn from_durations<I: IntoIterator<Item = Duration>>(durations: I) -> Option<Self> {
+        let mut ms: Vec<f64> = durations
+            .into_iter()
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .collect();
+        if ms.is_empty() {
+            return None;
+        }
+        ms.sort_by(f64::total_cmp);
+
+        // Nearest-rank percentile
+        let percentile =
+            |p: f64| ms[((p / 100.0 * ms.len() as f64).ceil() as usize).clamp(1, ms.len()) - 1];
+        Some(Self {
+            count: ms.len(),
+            min: ms[0],
+            avg: ms.iter().sum::<f64>() / ms.len() as f64,
+            max: ms[ms.len() - 1],
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:.2} avg {:.2} max {:.2} p50 {:.2} p95 {:.2} p99 {:.2} ms",
+            self.min, self.avg, self.max, self.p50, self.p95, self.p99
+        )
+    }
+}
+
+// Per-frame timings of a run, with rolling statistics over the last
+// `ROLLING_FRAMES` frames. Only those are kept unless the whole history was
+// asked for with `with_history`, for summaries and CSV files of the run.
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    recent: VecDeque<FrameSample>,
+    history: Option<Vec<FrameSample>>,
+    // Frames recorded so far
+    count: usize,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Keeps every frame instead of the last `ROLLING_FRAMES`
+    pub fn with_history() -> Self {
+        Self {
+            history: Some(Vec::new()),
+            ..Self::default()
+        }
+    }
+
+    // Returns the index of the frame, used to attach its GPU time later
+    pub fn record(&mut self, frame: Duration, cpu: Duration) -> usize {
+        let sample = FrameSample {
+            frame,
+            cpu,
+            gpu: None,
+        };
+        if self.recent.len() == ROLLING_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(sample);
+        if let Some(history) = &mut self.history {
+            history.push(sample);
+        }
+        self.count += 1;
+        self.count - 1
+    }
+
+    // Ignored for frames that are no longer kept
+    pub fn set_gpu_time(&mut self, frame: usize, gpu: Duration) {
+        let first_recent = self.count - self.recent.len();
+        if let Some(sample) = frame
+            .checked_sub(first_recent)
+            .and_then(|i| self.recent.get_mut(i))
+        {
+            sample.gpu = Some(gpu);
+        }
+        if let Some(sample) = self.history.as_mut().and_then(|h| h.get_mut(frame)) {
+            sample.gpu = Some(gpu);
+        }
+    }
+
+    // Every frame of the run with `with_history`, otherwise empty
+    pub fn samples(&self) -> &[FrameSample] {
+        self.history.as_deref().unwrap_or_default()
+    }
+
+    // The last `ROLLING_FRAMES` frames, oldest first
+    pub fn recent(&self) -> impl Iterator<Item = &FrameSample> {
+        self.recent.iter()
+    }
+
+    // Frames recorded so far, including those no longer kept
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Average frames per second over the rolling window
+    pub fn fps(&self) -> f64 {
+        let total: Duration = self.recent().map(|s| s.frame).sum();
+        if total.is_zero() {
+            return 0.0;
+        }
+        self.recent.len() as f64 / total.as_secs_f64()
+    }
+
+    pub fn rolling_frame(&self) -> Option<Summary> {
+        Summary::from_durations(self.recent().map(|s| s.frame))
+    }
+
+    pub fn rolling_cpu(&self) -> Option<Summary> {
+        Summary::from_durations(self.recent().map(|s| s.cpu))
+    }
+
+    pub fn rolling_gpu(&self) -> Option<Summary> {
+        Summary::from_durations(self.recent().filter_map(|s| s.gpu))
+    }
+
+    // The kept frames with their index: the whole run with `with_history`,
+    // the rolling window otherwise
+    fn kept(&self) -> Box<dyn Iterator<Item = (usize, &FrameSample)> + '_> {
+        match &self.history {
+            Some(history) => Box::new(history.iter().enumerate()),
+            None => {
+                let first = self.count - self.recent.len();
+                Box::new((first..).zip(&self.recent))
+            }
+        }
+    }
+
+    // Statistics of the kept frames, one line each
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("Frames: {}", self.count)];
+        let all = [
+            (
+                "Frame",
+                Summary::from_durations(self.kept().map(|(_, s)| s.frame)),
+            ),
+            (
+                "CPU",
+                Summary::from_durations(self.kept().map(|(_, s)| s.cpu)),
+            ),
+            (
+                "GPU",
+                Summary::from_durations(self.kept().filter_map(|(_, s)| s.gpu)),
+            ),
+        ];
+        for (name, summary) in all {
+            if let Some(summary) = summary {
+                lines.push(format!("{name}: {summary}"));
+            }
+        }
+        lines.join("\n")
+    }
+
+    // One line per kept frame: index, frame, CPU and GPU time in
+    // milliseconds. The GPU column is empty for frames without a query
+    // result.
+    pub fn write_csv(&self, path: &Path) -> Result<(), Error> {
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = std::fs::File::create(path).map_err(io_error)?;
+        let mut out = std::io::BufWriter::new(file);
+
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        writeln!(out, "frame,frame_ms,cpu_ms,gpu_ms").map_err(io_error)?;
+        for (i, sample) in self.kept() {
+            let gpu = sample
+                .gpu
+                .map(|d| format!("{:.4}", ms(d)))
+                .unwrap_or_default();
+            writeln!(
+                out,
+                "{i},{:.4},{:.4},{gpu}",
+                ms(sample.frame),
+                ms(sample.cpu)
+            )
+            .map_err(io_error)?;
+        }
+        out.flush().map_err(io_error)
+    }
+}
+
+// Measures GPU time with a ring of `GL_TIME_ELAPSED` queries. Needs a
+// current GL context. Deleted on Drop.
+pub struct GpuTimer {
+    queries: [u32; GPU_QUERIES],
+    // Frame each query measures, None when the query is free
+    pending: [Option<usize>; GPU_QUERIES],
+    next: usize,
+    running: bool,
+    // The first query of a fresh context can report garbage (llvmpipe returns
+    // the time since startup), so its result is dropped
+    discard_next_result: bool,
+}
+
+impl GpuTimer {
+    pub fn new() -> Self {
+        let mut queries = [0; GPU_QUERIES];
+        unsafe { gl::GenQueries(GPU_QUERIES as i32, queries.as_mut_ptr()) };
+        Self {
+            queries,
+            pending: [None; GPU_QUERIES],
+            next: 0,
+            running: false,
+            discard_next_result: true,
+        }
+    }
+
+    // Starts timing `frame`. Skipped when all queries are still waiting for
+    // results.
+    pub fn begin(&mut self, frame: usize) {
+        if self.running || self.pending[self.next].is_some() {
+            return;
+        }
+        unsafe { gl::BeginQuery(gl::TIME_ELAPSED, self.queries[self.next]) };
+        self.pending[self.next] = Some(frame);
+        self.running = true;
+    }
+
+    pub fn end(&mut self) {
+        if !self.running {
+            return;
+        }
+        unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+        self.running = false;
+        self.next = (self.next + 1) % GPU_QUERIES;
+    }
+
+    // Results that became available since the last call, as (frame, time)
+    pub fn poll(&mut self) -> Vec<(usize, Duration)> {
+        let mut results = Vec::new();
+        for (i, (query, pending)) in self.queries.iter().zip(&mut self.pending).enumerate() {
+            let Some(frame) = *pending else {
+                continue;
+            };
+            if self.running && i == self.next {
+                continue;
+            }
+
+            let mut available = 0;
+            unsafe { gl::GetQueryObjectiv(*query, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+            if available == 0 {
+                continue;
+            }
+            let mut nanos = 0_u64;
+            unsafe { gl::GetQueryObjectui64v(*query, gl::QUERY_RESULT, &mut nanos) };
+            *pending = None;
+            if std::mem::take(&mut self.discard_next_result) {
+                continue;
+            }
+            results.push((frame, Duration::from_nanos(nanos)));
+        }
+        results
+    }
+}
+
+impl Default for GpuTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteQueries(GPU_QUERIES as i32, self.queries.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn summary_percentiles() {
+        // Shuffled 1..=100 ms
+        let durations = (1..=100).map(|i| ms(i * 37 % 101));
+        let summary = Summary::from_durations(durations).unwrap();
+        assert_eq!(summary.count, 100);
+        assert_eq!((summary.min, summary.max), (1.0, 100.0));
+        assert!((summary.avg - 50.5).abs() < 1e-9);
+        // Nearest rank: the smallest value with at least p% at or below it
+        assert_eq!((summary.p50, summary.p95, summary.p99), (50.0, 95.0, 99.0));
+
+        let single = Summary::from_durations([ms(4)]).unwrap();
+        assert_eq!(
+            (single.min, single.p50, single.p99, single.max),
+            (4.0, 4.0, 4.0, 4.0)
+        );
+        assert_eq!(Summary::from_durations([]), None);
+        assert_eq!(
+            single.to_string(),
+            "min 4.00 avg 4.00 max 4.00 p50 4.00 p95 4.00 p99 4.00 ms"
+        );
+    }
+
+    #[test]
+    fn rolling_window() {
+        let mut stats = FrameStats::new();
+        for i in 0..ROLLING_FRAMES + 10 {
+            // The first 10 frames are slow and fall out of the window
+            let frame = if i < 10 { ms(100) } else { ms(10) };
+            assert_eq!(stats.record(frame, ms(2)), i);
+        }
+        assert_eq!(stats.len(), ROLLING_FRAMES + 10);
+        assert_eq!(stats.recent().count(), ROLLING_FRAMES);
+        assert!(stats.samples().is_empty());
+
+        let frame = stats.rolling_frame().unwrap();
+        assert_eq!((frame.min, frame.max), (10.0, 10.0));
+        assert!((stats.fps() - 100.0).abs() < 1e-9);
+        assert_eq!(stats.rolling_cpu().unwrap().avg, 2.0);
+        assert_eq!(stats.rolling_gpu(), None);
+    }
+
+    #[test]
+    fn late_gpu_times() {
+        let mut stats = FrameStats::new();
+        for _ in 0..ROLLING_FRAMES + 5 {
+            stats.record(ms(10), ms(1));
+        }
+        // Frame 3 has left the window, the last one is still in it
+        stats.set_gpu_time(3, ms(50));
+        stats.set_gpu_time(ROLLING_FRAMES + 4, ms(6));
+        stats.set_gpu_time(ROLLING_FRAMES + 5, ms(70));
+        let gpu = stats.rolling_gpu().unwrap();
+        assert_eq!((gpu.count, gpu.max), (1, 6.0));
+        assert_eq!(stats.recent().last().unwrap().gpu, Some(ms(6)));
+
+        let mut stats = FrameStats::with_history();
+        for _ in 0..ROLLING_FRAMES + 5 {
+            stats.record(ms(10), ms(1));
+        }
+        stats.set_gpu_time(3, ms(50));
+        assert_eq!(stats.samples().len(), ROLLING_FRAMES + 5);
+        assert_eq!(stats.samples()[3].gpu, Some(ms(50)));
+        // Only the history still has frame 3
+        assert_eq!(stats.rolling_gpu(), None);
+        assert!(stats.summary().contains("GPU: min 50.00"));
+    }
+
+    #[test]
+    fn summary_lines() {
+        let mut stats = FrameStats::with_history();
+        for i in 1..=4 {
+            stats.record(ms(i * 10), ms(i));
+        }
+        assert_eq!(
+            stats.summary(),
+            "Frames: 4\n\
+             Frame: min 10.00 avg 25.00 max 40.00 p50 20.00 p95 40.00 p99 40.00 ms\n\
+             CPU: min 1.00 avg 2.50 max 4.00 p50 2.00 p95 4.00 p99 4.00 ms"
+        );
+        assert_eq!(FrameStats::new().summary(), "Frames: 0");
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let mut stats = FrameStats::with_history();
+        stats.record(ms(16), ms(3));
+        let second = stats.record(Duration::from_micros(16_667), Duration::from_micros(2_500));
+        stats.set_gpu_time(second, Duration::from_micros(1_250));
+
+        let path =
+            std::env::temp_dir().join(format!("rust-triangle-{}-stats.csv", std::process::id()));
+        stats.write_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            csv,
+            "frame,frame_ms,cpu_ms,gpu_ms\n\
+             0,16.0000,3.0000,\n\
+             1,16.6670,2.5000,1.2500\n"
+        );
+
+        // Without the history the rows keep their frame index
+        let mut stats = FrameStats::new();
+        for _ in 0..ROLLING_FRAMES + 2 {
+            stats.record(ms(1), ms(1));
+        }
+        stats.write_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(rows.len(), ROLLING_FRAMES);
+        assert!(rows[0].starts_with("2,"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::shader::{TEXT_FRAG_SHADER, TEXT_VERT_SHADER, U_MVP};
+use crate::{
+    Attribute, Buffer, BufferTarget, BufferUsage, Color, Error, Filter, Font, Mat4, ShaderProgram,
+    Texture2D, TextureOptions, Vec2, Vertex, VertexArray, VertexLayout, Wrap,
+};
+
+// Corner of a glyph quad, in pixels, with its tint and atlas coordinates
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+    pub uv: [f32; 2],
+}
+
+impl Vertex for TextVertex {
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .with(Attribute::Position)
+            .with(Attribute::Color)
+            .with(Attribute::Uv)
+    }
+}
+
+// Draws strings as glyph quads over whatever is in the framebuffer. `draw`
+// only queues the quads, `flush` uploads and draws all of them at once.
+// Needs a current GL context.
+pub struct TextRenderer {
+    font: Font,
+    program: ShaderProgram,
+    atlas: Texture2D,
+    vertex_array: VertexArray,
+    vertex_buffer: Buffer<TextVertex>,
+    vertices: Vec<TextVertex>,
+}
+
+impl TextRenderer {
+    pub fn new(font: Font) -> Result<Self, Error> {
+        let program = ShaderProgram::from_sources(TEXT_VERT_SHADER, TEXT_FRAG_SHADER)?;
+        let (width, height) = font.atlas_size();
+        let mut max_size = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max_size) };
+        if width.max(height) > max_size as u32 {
+            return Err(Error::Font(format!(
+                "{width}x{height} glyph atlas exceeds the {max_size} pixel texture limit, \
+                 use a smaller font size"
+            )));
+        }
+        let options = TextureOptions {
+            filter: if font.smooth() {
+                Filter::Linear
+            } else {
+                Filter::Nearest
+            },
+            wrap: Wrap::Clamp,
+            mipmaps: false,
+        };
+        let atlas = Texture2D::from_r8(width, height, font.atlas(), &options);
+        let vertex_buffer = Buffer::new(BufferTarget::Array, BufferUsage::Stream, &[]);
+        let vertex_array = VertexArray::new();
+        vertex_array.set_vertex_buffer(&vertex_buffer);
+
+        Ok(Self {
+            font,
+            program,
+            atlas,
+            vertex_array,
+            vertex_buffer,
+            vertices: Vec::new(),
+        })
+    }
+
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    // Size of `text` drawn at `scale`, in pixels
+    pub fn measure(&self, text: &str, scale: f32) -> Vec2 {
+        self.font.measure(text) * scale
+    }
+
+    // Queues `text` with its top-left corner at `position`, in pixels from
+    // the top-left corner of the framebuffer. '\n' starts a new line.
+    pub fn draw(&mut self, text: &str, position: Vec2, scale: f32, color: Color) {
+        let (atlas_width, atlas_height) = self.font.atlas_size();
+        let (atlas_width, atlas_height) = (atlas_width as f32, atlas_height as f32);
+        let color = color.to_array();
+
+        let mut pen = position;
+        for c in text.chars() {
+            if c == '\n' {
+                pen = Vec2::new(position.x, pen.y + self.font.line_height() * scale);
+                continue;
+            }
+            let Some(glyph) = self.font.glyph(c) else {
+                continue;
+            };
+
+            let top_left = pen + glyph.offset * scale;
+            let bottom_right =
+                top_left + Vec2::new(glyph.width as f32, glyph.height as f32) * scale;
+            let (x0, y0, x1, y1) = (top_left.x, top_left.y, bottom_right.x, bottom_right.y);
+            let u0 = glyph.x as f32 / atlas_width;
+            let v0 = glyph.y as f32 / atlas_height;
+            let u1 = (glyph.x + glyph.width) as f32 / atlas_width;
+            let v1 = (glyph.y + glyph.height) as f32 / atlas_height;
+            let corner = |x: f32, y: f32, u: f32, v: f32| TextVertex {
+                position: [x, y, 0.0],
+                color,
+                uv: [u, v],
+            };
+            self.vertices.extend([
+                corner(x0, y0, u0, v0),
+                corner(x0, y1, u0, v1),
+                corner(x1, y1, u1, v1),
+                corner(x0, y0, u0, v0),
+                corner(x1, y1, u1, v1),
+                corner(x1, y0, u1, v0),
+            ]);
+            pen.x += glyph.advance * scale;
+        }
+    }
+
+    // Draws the queued text over the whole `width` x `height` framebuffer
+    // with alpha blending, then empties the queue. The GL viewport and
+    // blending state are restored afterwards.
+    pub fn flush(&mut self, width: i32, height: i32) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        self.vertex_buffer.set_data(&self.vertices);
+        self.vertex_buffer.unbind();
+        self.vertices.clear();
+
+        let projection = Mat4::orthographic(0.0, width as f32, height as f32, 0.0, -1.0, 1.0);
+        self.program.set_uniform(U_MVP, &projection);
+        self.program.bind();
+
+        let mut viewport = [0; 4];
+        unsafe {
+            let blend = gl::IsEnabled(gl::BLEND) == gl::TRUE;
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::Viewport(0, 0, width, height);
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
+            self.atlas.bind(0);
+
+            self.vertex_array.bind();
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_buffer.len() as i32);
+            self.vertex_array.unbind();
+
+            self.atlas.unbind(0);
+            if !blend {
+                gl::Disable(gl::BLEND);
+            }
+            gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    // Blocky, keeps pixel art sharp
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Filter::Nearest),
+            "linear" => Ok(Filter::Linear),
+            _ => Err(format!("Unknown texture filter: {s}")),
+        }
+    }
+}
+
+// What is sampled for coordinates outside 0.0 to 1.0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    Mirror,
+    Clamp,
+}
+
+impl Wrap {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::Mirror => gl::MIRRORED_REPEAT,
+            Wrap::Clamp => gl::CLAMP_TO_EDGE,
+        }
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "repeat" => Ok(Wrap::Repeat),
+            "mirror" => Ok(Wrap::Mirror),
+            "clamp" => Ok(Wrap::Clamp),
+            _ => Err(format!("Unknown texture wrap mode: {s}")),
+        }
+    }
+}
+
+// Sampling state of a `Texture2D`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub filter: Filter,
+    pub wrap: Wrap,
+    // Generate the mip chain and sample it when the texture is minified
+    pub mipmaps: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            filter: Filter::Linear,
+            wrap: Wrap::Repeat,
+            mipmaps: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Rgba8,
+    R8,
+}
+
+// GL 2D texture. Needs a current GL context, deleted on Drop.
+pub struct Texture2D {
+    id: u32,
+    width: u32,
+    height: u32,
+    options: TextureOptions,
+}
+
+impl Texture2D {
+    // Decodes a PNG, JPEG or BMP file. The rows are flipped so that v = 0 is
+    // the bottom of the image, as GL and OBJ texture coordinates expect.
+    pub fn load(path: &Path, options: &TextureOptions) -> Result<Self, Error> {
+        let image = image::open(path)
+            .map_err(|source| Error::Image {
+                path: path.to_path_buf(),
+                source,
+            })?
+            .flipv()
+            .into_rgba8();
+        Ok(Self::from_rgba8(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+            options,
+        ))
+    }
+
+    // `pixels` holds `width` x `height` RGBA texels, bottom row first
+    pub fn from_rgba8(width: u32, height: u32, pixels: &[u8], options: &TextureOptions) -> Self {
+        Self::new(Format::Rgba8, width, height, Some(pixels), options)
+    }
+
+    // Single channel texture, sampled as (r, 0, 0, 1)
+    pub fn from_r8(width: u32, height: u32, pixels: &[u8], options: &TextureOptions) -> Self {
+        Self::new(Format::R8, width, height, Some(pixels), options)
+    }
+
+    // RGBA texture with undefined contents, to render into
+    pub fn empty(width: u32, height: u32, options: &TextureOptions) -> Self {
+        Self::new(Format::Rgba8, width, height, None, options)
+    }
+
+    fn new(
+        format: Format,
+        width: u32,
+        height: u32,
+        pixels: Option<&[u8]>,
+        options: &TextureOptions,
+    ) -> Self {
+        let (internal_format, pixel_format, texel_size) = match format {
+            Format::Rgba8 => (gl::RGBA8, gl::RGBA, 4),
+            Format::R8 => (gl::R8, gl::RED, 1),
+        };
+        if let Some(pixels) = pixels {
+            assert_eq!(
+                pixels.len(),
+                width as usize * height as usize * texel_size,
+                "texture data does not match its size"
+            );
+        }
+
+        let mut texture = Self {
+            id: 0,
+            width,
+            height,
+            options: *options,
+        };
+        unsafe {
+            gl::GenTextures(1, &mut texture.id);
+            gl::BindTexture(gl::TEXTURE_2D, texture.id);
+            // Rows of single channel textures are not 4-byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                width as i32,
+                height as i32,
+                0,
+                pixel_format,
+                gl::UNSIGNED_BYTE,
+                pixels.map_or(std::ptr::null(), |pixels| pixels.as_ptr().cast()),
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            if options.mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+        texture.apply_options();
+        unsafe { gl::BindTexture(gl::TEXTURE_2D, 0) };
+        texture
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn options(&self) -> &TextureOptions {
+        &self.options
+    }
+
+    pub fn set_options(&mut self, options: &TextureOptions) {
+        let generate_mipmaps = options.mipmaps && !self.options.mipmaps;
+        self.options = *options;
+        self.bind(0);
+        if generate_mipmaps {
+            unsafe { gl::GenerateMipmap(gl::TEXTURE_2D) };
+        }
+        self.apply_options();
+        self.unbind(0);
+    }
+
+    // Binds the texture to `TEXTURE0 + unit`
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.id);
+        }
+    }
+
+    pub fn unbind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    // Sets the sampling parameters of the bound texture
+    fn apply_options(&self) {
+        let TextureOptions {
+            filter,
+            wrap,
+            mipmaps,
+        } = self.options;
+        let min_filter = match (filter, mipmaps) {
+            (Filter::Nearest, false) => gl::NEAREST,
+            (Filter::Linear, false) => gl::LINEAR,
+            (Filter::Nearest, true) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, true) => gl::LINEAR_MIPMAP_LINEAR,
+        };
+        let mag_filter = match filter {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        };
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, wrap.gl_enum() as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, wrap.gl_enum() as i32);
+        }
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.id) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_texture() {
+        // Fails reading the file, before anything is uploaded
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/textures/missing.png");
+        let result = Texture2D::load(&path, &TextureOptions::default());
+        assert!(matches!(result, Err(Error::Image { path: p, .. }) if p == path));
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+// Upper bound of updates run for one frame. After a long stall (window
+// dragged, debugger break) the simulation skips ahead instead of trying to
+// catch up and falling further behind.
+const MAX_UPDATES_PER_FRAME: u32 = 8;
+
+// Accumulates real time and hands it out in fixed `step`s, so the
+// simulation runs at the same speed whatever the frame rate is
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    last: Instant,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            last: Instant::now(),
+        }
+    }
+
+    // `rate` updates per second
+    pub fn from_rate(rate: u32) -> Self {
+        Self::new(Duration::from_secs_f64(1.0 / f64::from(rate.max(1))))
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    // Drops the accumulated time and starts measuring from now
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+        self.last = Instant::now();
+    }
+
+    // Adds the time since the last call and returns how many updates of
+    // `step` are due
+    pub fn advance(&mut self) -> u32 {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+        self.advance_by(elapsed)
+    }
+
+    // Same as `advance` with the time given instead of measured
+    pub fn advance_by(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+        let mut updates = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            updates += 1;
+            if updates == MAX_UPDATES_PER_FRAME {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+        }
+        updates
+    }
+
+    // How far the current frame is between the last update and the next one,
+    // from 0.0 to 1.0
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
+
+// Sleeps at the end of a frame to stay under a maximum frame rate
+pub struct FrameLimiter {
+    frame_time: Option<Duration>,
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    // 0 disables the limit
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            frame_time: (max_fps > 0).then(|| Duration::from_secs_f64(1.0 / f64::from(max_fps))),
+            next_frame: Instant::now(),
+        }
+    }
+
+    // None without a limit
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.frame_time
+    }
+
+    pub fn wait(&mut self) {
+        if let Some(delay) = self.delay(Instant::now()) {
+            std::thread::sleep(delay);
+        }
+    }
+
+    // How long to sleep when the frame ends at `now`
+    fn delay(&mut self, now: Instant) -> Option<Duration> {
+        let frame_time = self.frame_time?;
+        self.next_frame += frame_time;
+        if self.next_frame > now {
+            Some(self.next_frame - now)
+        } else {
+            // Running behind, don't rush the following frames to make up for it
+            self.next_frame = now;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn updates_per_frame() {
+        let mut timestep = FixedTimestep::new(MS * 10);
+        assert_eq!(timestep.advance_by(MS * 5), 0);
+        assert_eq!(timestep.advance_by(MS * 5), 1);
+        assert_eq!(timestep.advance_by(MS * 25), 2);
+        assert_eq!(timestep.advance_by(MS * 5), 1);
+        assert_eq!(timestep.advance_by(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn clamps_updates_after_a_stall() {
+        let mut timestep = FixedTimestep::new(MS * 10);
+        assert_eq!(
+            timestep.advance_by(Duration::from_secs(5)),
+            MAX_UPDATES_PER_FRAME
+        );
+        // The rest of the stall is dropped
+        assert_eq!(timestep.alpha(), 0.0);
+        assert_eq!(timestep.advance_by(MS * 10), 1);
+    }
+
+    #[test]
+    fn alpha() {
+        let mut timestep = FixedTimestep::new(MS * 10);
+        assert_eq!(timestep.alpha(), 0.0);
+        timestep.advance_by(MS * 2);
+        assert!((timestep.alpha() - 0.2).abs() < 1e-6);
+        timestep.advance_by(MS * 15);
+        assert!((timestep.alpha() - 0.7).abs() < 1e-6);
+        timestep.reset();
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn from_rate() {
+        assert_eq!(FixedTimestep::from_rate(100).step(), MS * 10);
+        assert_eq!(FixedTimestep::from_rate(1).step(), Duration::from_secs(1));
+        assert_eq!(FixedTimestep::from_rate(0).step(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn frame_limiter() {
+        assert_eq!(FrameLimiter::new(0).frame_time(), None);
+        let mut unlimited = FrameLimiter::new(0);
+        assert_eq!(unlimited.delay(Instant::now()), None);
+
+        let mut limiter = FrameLimiter::new(50);
+        assert_eq!(limiter.frame_time(), Some(MS * 20));
+        let start = limiter.next_frame;
+        // A fast frame sleeps for the rest of its time
+        assert_eq!(limiter.delay(start + MS * 5), Some(MS * 15));
+        assert_eq!(limiter.delay(start + MS * 30), Some(MS * 10));
+        // A late frame doesn't sleep, and the next one gets a full frame
+        assert_eq!(limiter.delay(start + MS * 100), None);
+        assert_eq!(limiter.delay(start + MS * 100), Some(MS * 20));
+    }
+}
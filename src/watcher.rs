@@ -0,0 +1,43 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Polls the modification time of a set of files. Cheap enough to call once
+// per frame from the render loop.
+pub struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let files = paths
+            .iter()
+            .map(|p| {
+                let path = p.as_ref().to_path_buf();
+                let modified = modified_time(&path);
+                (path, modified)
+            })
+            .collect();
+        Self { files }
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    // Returns true if any file changed since the last call
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in &mut self.files {
+            let modified = modified_time(path);
+            if modified != *last {
+                *last = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
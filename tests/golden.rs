@@ -0,0 +1,592 @@
+// Golden-image tests: render offscreen and compare against the reference PNGs
+// in `tests/golden`. Run with `UPDATE_GOLDEN=1` to rewrite the references.
+// On a mismatch the actual frame and a diff image are written to
+// `target/golden-diff`. Tests that need OpenGL fail without a headless EGL
+// context, set `GOLDEN_SKIP_NO_GL=1` to skip them instead.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rust_triangle::{
+    Camera, Color, Config, Effect, Error, Filter, Framebuffer, HeadlessApp, IndexBuffer, Mesh,
+    MeshData, Quat, Topology, Transform, Vec2, Vec3, capture, render_software,
+};
+
+// Max per-channel difference for two pixels to count as equal
+const CHANNEL_TOLERANCE: u8 = 2;
+// Fraction of pixels allowed to differ, covers edge rasterization differences
+// between drivers
+const MAX_MISMATCH_RATIO: f64 = 0.002;
+
+// Only one GL context is used at a time
+static GL_LOCK: Mutex<()> = Mutex::new(());
+
+fn models_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/models")
+}
+
+fn textures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/textures")
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn diff_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("target/golden-diff")
+}
+
+fn load_png(path: &Path) -> (u32, u32, Vec<u8>) {
+    let file = File::open(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    let mut reader = png::Decoder::new(BufReader::new(file)).read_info().unwrap();
+    let info = reader.info();
+    assert_eq!(
+        (info.color_type, info.bit_depth),
+        (png::ColorType::Rgba, png::BitDepth::Eight),
+        "{} is not an 8-bit RGBA PNG",
+        path.display()
+    );
+    let (width, height) = (info.width, info.height);
+    let mut pixels = vec![0; width as usize * height as usize * 4];
+    reader.next_frame(&mut pixels).unwrap();
+    (width, height, pixels)
+}
+
+// Creates the headless app of a test. Fails the test when that isn't
+// possible, unless `GOLDEN_SKIP_NO_GL=1` asks to skip it with None.
+fn headless_app(config: &Config) -> Option<HeadlessApp> {
+    match HeadlessApp::new(config) {
+        Ok(app) => Some(app),
+        Err(e) if std::env::var("GOLDEN_SKIP_NO_GL").is_ok_and(|skip| skip == "1") => {
+            eprintln!("Skipping golden test, no headless GL context: {e}");
+            None
+        }
+        Err(e) => panic!("No headless GL context: {e} (GOLDEN_SKIP_NO_GL=1 skips GL tests)"),
+    }
+}
+
+// Renders one frame headless, None when skipped
+fn render(config: &Config) -> Option<Vec<u8>> {
+    headless_app(config).map(|mut app| app.render())
+}
+
+// Compares `actual` against `tests/golden/<name>.png`
+fn assert_golden(name: &str, width: u32, height: u32, actual: &[u8]) {
+    let golden = golden_dir().join(format!("{name}.png"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        capture::save_png(&golden, width, height, actual).unwrap();
+        return;
+    }
+
+    let (golden_width, golden_height, expected) = load_png(&golden);
+    assert_eq!(
+        (golden_width, golden_height),
+        (width, height),
+        "{name}: frame size differs from the reference"
+    );
+    assert_matches(name, &golden, width, height, actual, &expected);
+}
+
+fn assert_matches(
+    name: &str,
+    reference: &Path,
+    width: u32,
+    height: u32,
+    actual: &[u8],
+    expected: &[u8],
+) {
+    let mut diff = vec![0_u8; actual.len()];
+    let mut mismatched = 0;
+    for ((a, e), d) in actual
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .zip(diff.chunks_exact_mut(4))
+    {
+        let max_delta = a.iter().zip(e).map(|(a, e)| a.abs_diff(*e)).max().unwrap();
+        if max_delta > CHANNEL_TOLERANCE {
+            mismatched += 1;
+            d.copy_from_slice(&[255, 0, 255, 255]);
+        } else {
+            // Dimmed reference so the mismatches stand out
+            d.copy_from_slice(&[e[0] / 4, e[1] / 4, e[2] / 4, 255]);
+        }
+    }
+
+    let ratio = mismatched as f64 / (width as f64 * height as f64);
+    if ratio > MAX_MISMATCH_RATIO {
+        let dir = diff_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let actual_path = dir.join(format!("{name}.actual.png"));
+        let diff_path = dir.join(format!("{name}.diff.png"));
+        capture::save_png(&actual_path, width, height, actual).unwrap();
+        capture::save_png(&diff_path, width, height, &diff).unwrap();
+        panic!(
+            "{name}: {mismatched} pixels differ from {} ({:.2}%), see {}",
+            reference.display(),
+            ratio * 100.0,
+            diff_path.display()
+        );
+    }
+}
+
+#[test]
+fn default_triangle() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    if let Some(frame) = render(&config) {
+        assert_golden("triangle", config.width, config.height, &frame);
+    }
+}
+
+#[test]
+fn default_triangle_software() {
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    let backend = render_software(&config).unwrap();
+    // Shares the reference with the OpenGL backend
+    let golden = golden_dir().join("triangle.png");
+    let (width, height, expected) = load_png(&golden);
+    assert_eq!(backend.size(), (width, height));
+    assert_matches(
+        "triangle_software",
+        &golden,
+        width,
+        height,
+        backend.pixels(),
+        &expected,
+    );
+}
+
+#[test]
+fn backends_match() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 256,
+        height: 256,
+        ..Config::default()
+    };
+    let Some(gl_frame) = render(&config) else {
+        return;
+    };
+    let software = render_software(&config).unwrap();
+    assert_matches(
+        "backends",
+        Path::new("OpenGL backend"),
+        config.width,
+        config.height,
+        software.pixels(),
+        &gl_frame,
+    );
+}
+
+#[test]
+fn gradient_triangle() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        gradient: true,
+        ..Config::default()
+    };
+    if let Some(frame) = render(&config) {
+        assert_golden("gradient", config.width, config.height, &frame);
+    }
+}
+
+#[test]
+fn gradient_triangle_software() {
+    let config = Config {
+        width: 320,
+        height: 160,
+        gradient: true,
+        ..Config::default()
+    };
+    let backend = render_software(&config).unwrap();
+    let golden = golden_dir().join("gradient.png");
+    let (width, height, expected) = load_png(&golden);
+    assert_eq!(backend.size(), (width, height));
+    assert_matches(
+        "gradient_software",
+        &golden,
+        width,
+        height,
+        backend.pixels(),
+        &expected,
+    );
+}
+
+#[test]
+fn camera_transform() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    let renderer = app.renderer_mut();
+    renderer.set_camera(Some(
+        Camera::perspective(60_f32.to_radians()).with_position(Vec3::new(0.5, 0.5, 2.0)),
+    ));
+    renderer.set_transform(
+        Transform::from_translation(Vec3::new(0.25, 0.0, -0.5))
+            .with_rotation(Quat::from_euler(0.6, 0.0, 0.3))
+            .with_scale(Vec3::splat(1.5)),
+    );
+    let frame = app.render();
+    assert_golden("camera_transform", config.width, config.height, &frame);
+}
+
+#[test]
+fn text_overlay() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    let text = app.text_mut();
+    text.draw("Hello, triangle!", Vec2::new(8.0, 8.0), 1.0, Color::WHITE);
+    text.draw(
+        "x2 0123456789\n{[(<~?>)]}",
+        Vec2::new(8.0, 100.0),
+        2.0,
+        Color::CYAN.with_alpha(0.75),
+    );
+    let frame = app.render();
+    assert_golden("text", config.width, config.height, &frame);
+}
+
+// Renders a model file turned to show three faces
+fn render_model(file: &str) -> Option<Vec<u8>> {
+    let config = Config {
+        width: 320,
+        height: 160,
+        model: Some(models_dir().join(file)),
+        ..Config::default()
+    };
+    let mut app = headless_app(&config)?;
+    app.renderer_mut()
+        .set_transform(Transform::from_rotation(Quat::from_euler(0.6, 0.5, 0.0)));
+    Some(app.render())
+}
+
+#[test]
+fn obj_model() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(frame) = render_model("cube.obj") {
+        assert_golden("cube", 320, 160, &frame);
+    }
+}
+
+#[test]
+fn gltf_model() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Same cube as cube.obj, so it shares the reference
+    if let Some(frame) = render_model("cube.gltf") {
+        assert_golden("cube", 320, 160, &frame);
+    }
+}
+
+#[test]
+fn model_formats_match() {
+    let obj = MeshData::load(&models_dir().join("cube.obj")).unwrap();
+    let gltf = MeshData::load(&models_dir().join("cube.gltf")).unwrap();
+    assert_eq!(obj.vertices.len(), 24);
+    assert_eq!(obj.indices.len(), 36);
+    assert_eq!(gltf.vertices.len(), obj.vertices.len());
+    assert_eq!(gltf.indices.len(), obj.indices.len());
+    assert_eq!(obj.bounds(), gltf.bounds());
+    assert_eq!(obj.bounds(), Some((Vec3::splat(-0.5), Vec3::splat(0.5))));
+}
+
+#[test]
+fn grid_lines() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    let grid = Mesh::new(&MeshData::grid(8, 4)).unwrap();
+    assert!(matches!(grid.index_buffer(), Some(IndexBuffer::U16(_))));
+    assert_eq!(grid.topology(), Topology::Lines);
+
+    let renderer = app.renderer_mut();
+    renderer.set_model(Some(grid));
+    renderer.set_transform(Transform::from_scale(Vec3::splat(1.8)));
+    let frame = app.render();
+    assert_golden("grid", config.width, config.height, &frame);
+}
+
+// Renders an image file on a quad, magnified without filtering
+fn render_texture(file: &str) -> Option<Vec<u8>> {
+    let config = Config {
+        width: 320,
+        height: 160,
+        texture: Some(textures_dir().join(file)),
+        texture_filter: Filter::Nearest,
+        ..Config::default()
+    };
+    render(&config)
+}
+
+#[test]
+fn png_texture() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(frame) = render_texture("checker.png") {
+        assert_golden("texture", 320, 160, &frame);
+    }
+}
+
+#[test]
+fn bmp_texture() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Same pixels as checker.png, so it shares the reference
+    if let Some(frame) = render_texture("checker.bmp") {
+        assert_golden("texture", 320, 160, &frame);
+    }
+}
+
+#[test]
+fn triangle_msaa() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        samples: 4,
+        ..Config::default()
+    };
+    if let Some(frame) = render(&config) {
+        assert_golden("triangle_msaa", config.width, config.height, &frame);
+    }
+}
+
+#[test]
+fn headless_resize() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 64,
+        height: 64,
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    app.resize(320, 160).unwrap();
+    assert_eq!(app.size(), (320, 160));
+    let frame = app.render();
+    assert_golden("triangle", 320, 160, &frame);
+}
+
+#[test]
+fn headless_gl_version() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 64,
+        height: 64,
+        gl_version: (3, 3),
+        ..Config::default()
+    };
+    if headless_app(&config).is_none() {
+        return;
+    }
+    // A version no driver offers fails creating the context, not later
+    let config = Config {
+        gl_version: (9, 9),
+        ..config
+    };
+    let error = HeadlessApp::new(&config).err().expect("no GL 9.9 context");
+    assert!(
+        matches!(&error, Error::Headless(message) if message.contains("eglCreateContext failed")),
+        "{error}"
+    );
+}
+
+#[test]
+fn render_to_texture() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    let framebuffer = Framebuffer::new(320, 160, 0).unwrap();
+    let texture = framebuffer
+        .color_texture()
+        .expect("single-sampled color texture");
+    assert_eq!(texture.size(), (320, 160));
+    framebuffer.bind();
+    app.renderer_mut().render(1.0);
+    let frame = framebuffer.read_pixels();
+    framebuffer.unbind();
+    assert_golden("triangle", 320, 160, &frame);
+
+    // Same frame multisampled, resolved into the texture
+    let multisampled = Framebuffer::new(320, 160, 4).unwrap();
+    assert!(multisampled.color_texture().is_none());
+    multisampled.bind();
+    app.renderer_mut().render(1.0);
+    multisampled.resolve(&framebuffer);
+    let frame = framebuffer.read_pixels();
+    framebuffer.unbind();
+    assert_golden("triangle_msaa", 320, 160, &frame);
+}
+
+// Renders the default triangle with `effects` applied
+fn render_effects(effects: Vec<Effect>) -> Option<Vec<u8>> {
+    let config = Config {
+        width: 320,
+        height: 160,
+        effects,
+        ..Config::default()
+    };
+    render(&config)
+}
+
+#[test]
+fn effect_chain() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        gradient: true,
+        effects: vec![
+            Effect::Fxaa,
+            Effect::Blur,
+            Effect::Sharpen,
+            Effect::ToneMap,
+            Effect::Vignette,
+        ],
+        ..Config::default()
+    };
+    if let Some(frame) = render(&config) {
+        assert_golden("effects", config.width, config.height, &frame);
+    }
+}
+
+#[test]
+fn invert_and_grayscale_effects() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(inverted) = render_effects(vec![Effect::Invert]) else {
+        return;
+    };
+    let golden = golden_dir().join("triangle.png");
+    let (width, height, mut expected) = load_png(&golden);
+    for pixel in expected.chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = 255 - *channel;
+        }
+    }
+    assert_matches("invert", &golden, width, height, &inverted, &expected);
+
+    let gray = render_effects(vec![Effect::Grayscale, Effect::Invert]).unwrap();
+    for pixel in gray.chunks_exact(4) {
+        assert!(
+            pixel[0].abs_diff(pixel[1]) <= 1 && pixel[1].abs_diff(pixel[2]) <= 1,
+            "{pixel:?} is not gray"
+        );
+    }
+}
+
+#[test]
+fn shader_file_effect() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/shaders/swap_red_blue.frag");
+    let Some(frame) = render_effects(vec![Effect::Shader(path)]) else {
+        return;
+    };
+    let golden = golden_dir().join("triangle.png");
+    let (width, height, mut expected) = load_png(&golden);
+    for pixel in expected.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    assert_matches("swap_red_blue", &golden, width, height, &frame, &expected);
+}
+
+fn capture_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("target/capture-test")
+        .join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn scenes_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenes")
+}
+
+#[test]
+fn scene_file() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = Config {
+        width: 320,
+        height: 160,
+        scene: Some(scenes_dir().join("shapes.toml")),
+        ..Config::default()
+    };
+    if let Some(frame) = render(&config) {
+        assert_golden("scene", config.width, config.height, &frame);
+    }
+}
+
+#[test]
+fn scene_hot_reload() {
+    let _lock = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = capture_dir("scene-reload");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("scene.toml");
+    let write_scene = |color: &str, modified: SystemTime| {
+        let scene = format!("[[objects]]\nshape = \"quad\"\nsize = [2, 2]\ncolor = \"{color}\"\n");
+        std::fs::write(&path, scene).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    };
+    write_scene("red", UNIX_EPOCH + Duration::from_secs(1));
+
+    let config = Config {
+        width: 64,
+        height: 64,
+        scene: Some(path.clone()),
+        ..Config::default()
+    };
+    let Some(mut app) = headless_app(&config) else {
+        return;
+    };
+    assert_eq!(&app.render()[..4], &[255, 0, 0, 255]);
+
+    // A broken file keeps the last good scene
+    std::fs::write(&path, "[[objects]]\nshape = \"quad\"\nsize = [1]\n").unwrap();
+    app.renderer_mut().hot_reload();
+    assert_eq!(&app.render()[..4], &[255, 0, 0, 255]);
+
+    write_scene("blue", UNIX_EPOCH + Duration::from_secs(2));
+    app.renderer_mut().hot_reload();
+    assert_eq!(&app.render()[..4], &[0, 0, 255, 255]);
+}
@@ -0,0 +1,8 @@
+#version 330 core
+in vec2 v_uv;
+uniform sampler2D u_scene;
+out vec4 Color;
+void main()
+{
+    Color = texture(u_scene, v_uv).bgra;
+}